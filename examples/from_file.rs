@@ -0,0 +1,11 @@
+use json_to_struct::json2struct;
+
+json2struct!(Company @debug @store_json @file("examples/fixtures/company.json"));
+
+fn main() {
+    let json = (*COMPANY_JSON_VALUE).clone();
+
+    let company: Company = serde_json::from_value(json).unwrap();
+
+    println!("{:#?}", company);
+}
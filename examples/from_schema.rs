@@ -0,0 +1,17 @@
+use json_to_struct::json2struct_schema;
+
+json2struct_schema!(@debug "examples/fixtures/order.schema.json");
+
+fn main() {
+    let order: Order = serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "status": "shipped",
+        "customer": { "name": "John", "vip": true },
+        "items": [{ "sku": "A-1", "quantity": 2, "price": 9.5 }],
+        "payment": { "method": "card", "last4": "4242" },
+        "metadata": { "gift": "yes" }
+    }))
+    .unwrap();
+
+    println!("{:#?}", order);
+}
@@ -0,0 +1,13 @@
+use json_to_struct::json2struct;
+
+// `closed_at` and `assignee` are only in some of the samples, so they become `Option<String>`
+json2struct!(Ticket @debug @samples([
+    { "id": 1, "state": "open", "assignee": "ana" },
+    { "id": 2, "state": "closed", "closed_at": "2024-01-01" }
+]));
+
+fn main() {
+    let ticket: Ticket = serde_json::from_str(r#"{"id": 3, "state": "open"}"#).unwrap();
+
+    println!("{:#?}", ticket);
+}
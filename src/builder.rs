@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+
+use quote::{format_ident, quote};
+use syn::{ext::IdentExt, Ident};
+
+use crate::naming;
+use crate::parser::JsonMacroFlags;
+
+/// A field of a struct that gets a builder
+pub struct BuilderField {
+    pub name: Ident,
+    /// The type of the field's values, without the `Option` of optional fields
+    pub ty: proc_macro2::TokenStream,
+    /// Whether the field can be left unset, it's then `None`
+    pub optional: bool,
+    /// The generated struct the field holds and its builder, for a closure-based setter
+    pub nested: Option<(Ident, Ident)>,
+}
+
+/// The name the builder of a generated struct would like to have, `User` → `UserBuilder`
+pub fn builder_type_name(struct_name: &Ident) -> Ident {
+    format_ident!("{}Builder", struct_name)
+}
+
+/// Generates a builder for a struct, with a chainable setter per field.
+///
+/// `Example`
+///
+/// ```rust,ignore
+/// let user = User::builder()
+///     .name("John")
+///     .details_with(|details| details.email("john@example.com"))
+///     .build()?;
+/// ```
+///
+/// Setters are named after their field, unless the name is taken by `build()` or a closure-based setter,
+/// a `build` field gets `build_2`.
+///
+/// # Parameters
+/// - `struct_name`: The name of the struct
+/// - `builder_name`: The name of the builder
+/// - `fields`: The fields of the struct
+/// - `error_name`: The error returned when required fields are missing
+/// - `flags`: The macro flags
+///
+/// # Returns
+/// The builder struct and its impls
+pub fn builder_impl(
+    struct_name: &Ident,
+    builder_name: &Ident,
+    fields: &[BuilderField],
+    error_name: &Ident,
+    flags: &JsonMacroFlags,
+) -> proc_macro2::TokenStream {
+    let vis = &flags.vis;
+
+    // Plain setters are named first, so they keep their field's name over a closure-based setter
+    let mut taken = HashSet::from(["build".to_string()]);
+    let setter_names = fields
+        .iter()
+        .map(|field| naming::field_ident(&field.name.unraw().to_string(), &mut taken))
+        .collect::<Vec<_>>();
+    let with_names = fields
+        .iter()
+        .map(|field| {
+            let with_name = format!("{}_with", field.name.unraw());
+            field
+                .nested
+                .as_ref()
+                .map(|_| naming::field_ident(&with_name, &mut taken))
+        })
+        .collect::<Vec<_>>();
+
+    let debug = if flags.debug {
+        quote!(::std::fmt::Debug,)
+    } else {
+        quote!()
+    };
+
+    // Every field is stored as an `Option` until `build()`
+    let storage = fields.iter().map(|field| {
+        let BuilderField { name, ty, .. } = field;
+        quote!(#name: ::std::option::Option<#ty>)
+    });
+
+    let setters = fields
+        .iter()
+        .zip(setter_names.iter().zip(&with_names))
+        .map(|(field, (setter_name, with_name))| {
+            let BuilderField { name, ty, .. } = field;
+            let doc = format!(" Sets `{}`", name.unraw());
+
+            // Nested structs can be built in place, their missing fields are reported with a prefix
+            let (Some((nested, nested_builder)), Some(with_name)) = (&field.nested, with_name)
+            else {
+                return quote! {
+                    #[doc = #doc]
+                    pub fn #setter_name(mut self, value: impl ::std::convert::Into<#ty>) -> Self {
+                        self.#name = ::std::option::Option::Some(value.into());
+                        self
+                    }
+                };
+            };
+
+            let with_doc = format!(" Builds `{}` with a [`{}`]", name.unraw(), nested_builder);
+            let prefix = format!("{}.", name.unraw());
+
+            // Setting the field again replaces what a failed nested builder reported
+            quote! {
+                #[doc = #doc]
+                pub fn #setter_name(mut self, value: impl ::std::convert::Into<#ty>) -> Self {
+                    self.__missing.retain(|field| !field.starts_with(#prefix));
+                    self.#name = ::std::option::Option::Some(value.into());
+                    self
+                }
+
+                #[doc = #with_doc]
+                pub fn #with_name(
+                    mut self,
+                    build: impl ::std::ops::FnOnce(#nested_builder) -> #nested_builder,
+                ) -> Self {
+                    self.__missing.retain(|field| !field.starts_with(#prefix));
+                    match build(#nested::builder()).build() {
+                        ::std::result::Result::Ok(value) => {
+                            self.#name = ::std::option::Option::Some(value.into());
+                        }
+                        ::std::result::Result::Err(error) => {
+                            self.#name = ::std::option::Option::None;
+                            self.__missing.extend(
+                                error.missing.into_iter().map(|field| ::std::format!("{}{}", #prefix, field)),
+                            );
+                        }
+                    }
+                    self
+                }
+            }
+        });
+
+    // Required fields are checked all at once, so the error lists every missing one
+    let required = fields
+        .iter()
+        .filter(|field| !field.optional)
+        .collect::<Vec<_>>();
+
+    let required_names = required.iter().map(|field| &field.name).collect::<Vec<_>>();
+    let required_keys = required
+        .iter()
+        .map(|field| field.name.unraw().to_string())
+        .collect::<Vec<_>>();
+
+    let values = fields.iter().map(|field| {
+        let name = &field.name;
+
+        if field.optional {
+            quote!(#name: self.#name)
+        } else {
+            quote!(#name)
+        }
+    });
+
+    let struct_doc = format!(" Builds a [`{}`] field by field", struct_name);
+    let builder_doc = format!(" Starts building a [`{}`]", struct_name);
+    let build_doc = format!(
+        " Builds the [`{}`], failing with every required field that wasn't set",
+        struct_name
+    );
+
+    quote! {
+        #[doc = #struct_doc]
+        #[derive(#debug ::std::clone::Clone, ::std::default::Default)]
+        #vis struct #builder_name {
+            #(#storage,)*
+            /// Fields missing from nested builders
+            __missing: ::std::vec::Vec<::std::string::String>,
+        }
+
+        impl #struct_name {
+            #[doc = #builder_doc]
+            pub fn builder() -> #builder_name {
+                ::std::default::Default::default()
+            }
+        }
+
+        impl #builder_name {
+            #(#setters)*
+
+            #[doc = #build_doc]
+            pub fn build(self) -> ::std::result::Result<#struct_name, #error_name> {
+                #(let #required_names = self.#required_names.ok_or(#required_keys);)*
+
+                // Fields whose nested builder failed are already reported, with the fields it missed
+                let mut __missing = self.__missing;
+                #(
+                    if let ::std::result::Result::Err(field) = &#required_names {
+                        let prefix = ::std::format!("{}.", field);
+                        if !__missing.iter().any(|missing| missing.starts_with(&prefix)) {
+                            __missing.push(::std::string::ToString::to_string(field));
+                        }
+                    }
+                )*
+
+                match (#(#required_names,)*) {
+                    (#(::std::result::Result::Ok(#required_names),)*) if __missing.is_empty() => {
+                        ::std::result::Result::Ok(#struct_name { #(#values),* })
+                    }
+                    _ => ::std::result::Result::Err(#error_name { missing: __missing }),
+                }
+            }
+        }
+    }
+}
+
+/// Generates the error every builder returns when required fields are missing.
+///
+/// # Parameters
+/// - `error_name`: The name of the error
+/// - `flags`: The macro flags
+pub fn build_error(error_name: &Ident, flags: &JsonMacroFlags) -> proc_macro2::TokenStream {
+    let vis = &flags.vis;
+
+    quote! {
+        /// The required fields that weren't set when building a struct, nested ones are prefixed with their parent, `details.email`
+        #[derive(::std::fmt::Debug, ::std::clone::Clone, ::std::cmp::PartialEq)]
+        #vis struct #error_name {
+            pub missing: ::std::vec::Vec<::std::string::String>,
+        }
+
+        impl ::std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::write!(f, "missing required fields: {}", self.missing.join(", "))
+            }
+        }
+
+        impl ::std::error::Error for #error_name {}
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+
+use quote::{format_ident, ToTokens};
+use syn::Ident;
+
+use crate::generator::integer_type;
+use crate::naming::{self, Names};
+use crate::parser::{DedupeStrategy, JsonMacroFlags};
+use crate::shape::{FieldShape, Shape};
+
+/// A nested object shape, and the keys of every field it appears under
+struct Occurrences {
+    signature: String,
+    keys: Vec<String>,
+    /// The type name each key would give, singular for array elements
+    names: Vec<String>,
+}
+
+/// Finds the nested object shapes that are generated once and reused.
+///
+/// Two objects share a struct when their signatures match, which happens when they have the same
+/// field names and the same field types.
+///
+/// # Parameters
+/// - `fields`: The fields of the main struct
+/// - `base_name`: The name of the main struct
+/// - `flags`: The macro flags, `@dedupe(...)` picks how shared structs are named and `@share(...)` names them
+/// - `names`: The type names taken so far, the names of the shared structs are claimed in it
+///
+/// # Returns
+/// The signatures of the shared shapes, with their name if it's known up front,
+/// `None` meaning the name of the first object that's generated is used
+///
+/// # Errors
+/// If a `@share(...)` name doesn't match any nested object, or is already used
+pub fn shared_types(
+    fields: &[FieldShape],
+    base_name: &Ident,
+    flags: &JsonMacroFlags,
+    names: &mut Names,
+) -> syn::Result<HashMap<String, Option<Ident>>> {
+    let mut occurrences = Vec::new();
+    let mut definitions = HashSet::new();
+
+    for field in fields {
+        collect(
+            &field.shape,
+            &field.key,
+            false,
+            flags,
+            &mut definitions,
+            &mut occurrences,
+        );
+    }
+
+    // `@share(...)` names are used as they are, so they can't be taken twice
+    for (name, _) in &flags.share {
+        if !names.reserve(name) {
+            return Err(syn::Error::new(
+                name.span(),
+                format!("@share({}): the name `{}` is already used", name, name),
+            ));
+        }
+    }
+
+    let mut shared = HashMap::new();
+
+    // `@share(Address = "billing")` names the shape found under that key
+    for (name, key) in &flags.share {
+        let Some(key) = key else {
+            continue;
+        };
+
+        let occurrence = occurrences
+            .iter()
+            .find(|occurrence| occurrence.keys.contains(key))
+            .ok_or_else(|| {
+                syn::Error::new(
+                    name.span(),
+                    format!(
+                        "@share({} = \"{}\"): no nested object under `{}`",
+                        name, key, key
+                    ),
+                )
+            })?;
+
+        shared.insert(occurrence.signature.clone(), Some(name.clone()));
+    }
+
+    // Shapes that appear more than once, in the order they were first seen
+    let mut repeated = occurrences
+        .iter()
+        .filter(|occurrence| occurrence.keys.len() > 1)
+        .filter(|occurrence| !shared.contains_key(&occurrence.signature))
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    // `@share(Address)` names the repeated shapes in order
+    for (name, key) in &flags.share {
+        if key.is_some() {
+            continue;
+        }
+
+        let occurrence = repeated.next().ok_or_else(|| {
+            syn::Error::new(
+                name.span(),
+                format!(
+                    "@share({}): no repeated nested object left to name, use @share({} = \"key\")",
+                    name, name
+                ),
+            )
+        })?;
+
+        shared.insert(occurrence.signature.clone(), Some(name.clone()));
+    }
+
+    // Everything else follows the `@dedupe(...)` strategy
+    for occurrence in repeated {
+        let name = match flags.dedupe {
+            DedupeStrategy::Off => continue,
+            DedupeStrategy::First => None,
+            // `billing` and `shipping` have no name that fits both, so they share `Billing`
+            DedupeStrategy::Common => common_suffix(&occurrence.names).map(|suffix| {
+                let candidate = if flags.prefix_nested {
+                    format_ident!("{}{}", base_name, suffix)
+                } else {
+                    format_ident!("{}", suffix)
+                };
+
+                names.claim(&candidate, base_name)
+            }),
+        };
+
+        shared.insert(occurrence.signature.clone(), name);
+    }
+
+    Ok(shared)
+}
+
+/// Records every nested object shape, with the key it appears under.
+///
+/// `singular` is set for the elements of arrays, so `employees` is recorded as `Employee`.
+/// Schema definitions are generated once under their own name, so only the objects nested in them
+/// are recorded, the first time they're referred to.
+fn collect<'a>(
+    shape: &'a Shape,
+    key: &str,
+    singular: bool,
+    flags: &JsonMacroFlags,
+    definitions: &mut HashSet<&'a str>,
+    out: &mut Vec<Occurrences>,
+) {
+    match shape {
+        Shape::Named { reference, .. } if !definitions.insert(reference) => {}
+        Shape::Named {
+            shape: named_shape, ..
+        } => match named_shape.as_ref() {
+            Shape::Object(fields) => {
+                for field in fields {
+                    collect(&field.shape, &field.key, false, flags, definitions, out);
+                }
+            }
+            named_shape => collect(named_shape, key, singular, flags, definitions, out),
+        },
+        Shape::Object(fields) => {
+            let signature = signature(shape, flags);
+            let name = naming::type_name(key, singular);
+
+            match out
+                .iter_mut()
+                .find(|occurrence| occurrence.signature == signature)
+            {
+                Some(occurrence) => {
+                    occurrence.keys.push(key.to_string());
+                    occurrence.names.push(name);
+                }
+                None => out.push(Occurrences {
+                    signature,
+                    keys: vec![key.to_string()],
+                    names: vec![name],
+                }),
+            }
+
+            for field in fields {
+                collect(&field.shape, &field.key, false, flags, definitions, out);
+            }
+        }
+        Shape::Array(inner) | Shape::Map(inner) => {
+            collect(inner, key, true, flags, definitions, out)
+        }
+        Shape::Optional(inner) => collect(inner, key, singular, flags, definitions, out),
+        Shape::Union(members) => {
+            for member in members {
+                collect(member, key, singular, flags, definitions, out);
+            }
+        }
+        Shape::Tagged { variants, .. } => {
+            for variant in variants {
+                for field in &variant.fields {
+                    collect(&field.shape, &field.key, false, flags, definitions, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Describes the Rust type a shape generates, without the names of the generated structs.
+///
+/// Shapes with the same signature generate identical types.
+pub fn signature(shape: &Shape, flags: &JsonMacroFlags) -> String {
+    match shape {
+        Shape::Unknown | Shape::Any => "Value".to_string(),
+        Shape::Null => "null".to_string(),
+        Shape::Bool => "bool".to_string(),
+        Shape::Int { min, max } => integer_type(*min, *max, flags)
+            .expect("integer shapes always have a type")
+            .to_string(),
+        Shape::Float => "f64".to_string(),
+        Shape::Str => "String".to_string(),
+        Shape::Explicit(ty) => ty.to_token_stream().to_string(),
+        Shape::Array(inner) => format!("[{}]", signature(inner, flags)),
+        Shape::Map(inner) => format!("{{{}}}", signature(inner, flags)),
+        Shape::Enum(values) => format!("enum({})", values.join("|")),
+        // Every `$ref` to a definition gives the same type
+        Shape::Named { reference, .. } => format!("@{}", reference),
+        Shape::Recursive(reference) => format!("^{}", reference),
+        Shape::Optional(inner) => format!("?{}", signature(inner, flags)),
+        Shape::Object(fields) => fields_signature(fields, flags),
+        Shape::Union(members) => format!(
+            "({})",
+            members
+                .iter()
+                .map(|member| signature(member, flags))
+                .collect::<Vec<_>>()
+                .join("|")
+        ),
+        Shape::Tagged { tag, variants } => format!(
+            "{}({})",
+            tag,
+            variants
+                .iter()
+                .map(|variant| format!(
+                    "{}={}",
+                    variant.tag_value,
+                    fields_signature(&variant.fields, flags)
+                ))
+                .collect::<Vec<_>>()
+                .join("|")
+        ),
+    }
+}
+
+/// Describes the fields of an object, in order
+fn fields_signature(fields: &[FieldShape], flags: &JsonMacroFlags) -> String {
+    let fields = fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{:?}{}:{}",
+                field.key,
+                if field.missing { "?" } else { "" },
+                signature(&field.shape, flags)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Finds the words every name ends with, `BillingAddress` and `ShippingAddress` give `Address`
+fn common_suffix(names: &[String]) -> Option<String> {
+    let words = names
+        .iter()
+        .map(|name| naming::split_words(name))
+        .collect::<Vec<_>>();
+
+    let first = words.first()?;
+
+    let len = (1..=first.len())
+        .take_while(|len| {
+            let suffix = &first[first.len() - len..];
+            words.iter().all(|other| other.ends_with(suffix))
+        })
+        .last()?;
+
+    Some(naming::type_name(
+        &first[first.len() - len..].join("_"),
+        false,
+    ))
+}
@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+
+use quote::{format_ident, quote};
+use syn::Ident;
+
+use crate::parser::JsonMacroFlags;
+
+/// A field reachable from a struct, a variant of its `{Name}Field` enum
+#[derive(Clone)]
+pub struct AccessorField {
+    /// The variant, nested fields are prefixed with their parent's, `DetailsEmail`
+    pub variant: Ident,
+    /// The name of the field in the JSON
+    pub wire_name: String,
+    /// The RFC 6901 JSON pointer to the field, `/details/email`
+    pub pointer: String,
+    /// The field names leading to the field, `details.email`
+    pub access: Vec<Ident>,
+}
+
+impl AccessorField {
+    /// A field of the struct itself.
+    ///
+    /// # Parameters
+    /// - `variant`: The variant of the field
+    /// - `field_name`: The name of the field
+    /// - `wire_name`: The name of the field in the JSON
+    pub fn new(variant: Ident, field_name: &Ident, wire_name: String) -> Self {
+        // `~` and `/` are the only characters escaped in a JSON pointer
+        let pointer = format!("/{}", wire_name.replace('~', "~0").replace('/', "~1"));
+
+        AccessorField {
+            variant,
+            wire_name,
+            pointer,
+            access: vec![field_name.clone()],
+        }
+    }
+
+    /// A field of the struct this field holds, `details` and `email` → `details.email`
+    pub fn nested(&self, child: &AccessorField) -> Self {
+        AccessorField {
+            variant: format_ident!("{}{}", self.variant, child.variant),
+            wire_name: child.wire_name.clone(),
+            pointer: format!("{}{}", self.pointer, child.pointer),
+            access: self.access.iter().chain(&child.access).cloned().collect(),
+        }
+    }
+}
+
+/// The name the field enum of a generated struct would like to have, `User` → `UserField`
+pub fn field_enum_name(struct_name: &Ident) -> Ident {
+    format_ident!("{}Field", struct_name)
+}
+
+/// Makes the variants unique, a `details_email` field next to `details.email` gives `DetailsEmail2`.
+///
+/// # Parameters
+/// - `fields`: The fields, in the order they're declared
+pub fn unique_variants(fields: &mut [AccessorField]) {
+    let mut taken = HashSet::new();
+
+    for field in fields {
+        let variant = field.variant.to_string();
+
+        if !taken.insert(variant.clone()) {
+            field.variant = (2usize..)
+                .map(|n| format_ident!("{}{}", variant, n))
+                .find(|name| !taken.contains(&name.to_string()))
+                .unwrap_or_else(|| field.variant.clone());
+
+            taken.insert(field.variant.to_string());
+        }
+    }
+}
+
+/// Generates the `{Name}Field` enum of a struct, with the struct's `get` and `set`.
+///
+/// `Example`
+///
+/// ```rust,ignore
+/// user.set(UserField::DetailsEmail, json!("john@example.com"))?;
+///
+/// assert_eq!(user.get(UserField::DetailsEmail), json!("john@example.com"));
+/// assert_eq!(UserField::DetailsEmail.pointer(), "/details/email");
+/// ```
+///
+/// # Parameters
+/// - `struct_name`: The name of the struct
+/// - `enum_name`: The name of the field enum
+/// - `fields`: The fields reachable from the struct, see [`AccessorField`]
+/// - `flags`: The macro flags
+///
+/// # Returns
+/// The enum and its impls, or nothing for a struct without fields
+pub fn fields_impl(
+    struct_name: &Ident,
+    enum_name: &Ident,
+    fields: &[AccessorField],
+    flags: &JsonMacroFlags,
+) -> proc_macro2::TokenStream {
+    if fields.is_empty() {
+        return quote!();
+    }
+
+    let vis = &flags.vis;
+
+    let variants = fields
+        .iter()
+        .map(|field| &field.variant)
+        .collect::<Vec<_>>();
+    let wire_names = fields.iter().map(|field| &field.wire_name);
+    let pointers = fields.iter().map(|field| &field.pointer);
+
+    let variant_docs = fields.iter().map(|field| format!(" `{}`", field.pointer));
+    let accesses = fields
+        .iter()
+        .map(|field| {
+            let access = &field.access;
+            quote!(#(#access).*)
+        })
+        .collect::<Vec<_>>();
+
+    let enum_doc = format!(
+        " The fields of [`{}`], with the fields of the structs nested in it",
+        struct_name
+    );
+
+    quote! {
+        #[doc = #enum_doc]
+        #[derive(::std::fmt::Debug, ::std::clone::Clone, ::std::marker::Copy, ::std::cmp::PartialEq, ::std::cmp::Eq, ::std::hash::Hash)]
+        #vis enum #enum_name {
+            #(
+                #[doc = #variant_docs]
+                #variants,
+            )*
+        }
+
+        impl #enum_name {
+            /// Every field, in the order they're declared
+            pub const ALL: &'static [Self] = &[#(Self::#variants),*];
+
+            /// The name of the field in the JSON
+            pub fn name(self) -> &'static str {
+                match self {
+                    #(Self::#variants => #wire_names,)*
+                }
+            }
+
+            /// The JSON pointer to the field, `/details/email`
+            pub fn pointer(self) -> &'static str {
+                match self {
+                    #(Self::#variants => #pointers,)*
+                }
+            }
+        }
+
+        impl #struct_name {
+            /// Reads a field as JSON
+            pub fn get(&self, field: #enum_name) -> ::serde_json::Value {
+                match field {
+                    #(
+                        #enum_name::#variants => ::serde_json::to_value(&self.#accesses)
+                            .expect("the fields of generated structs serialize to JSON"),
+                    )*
+                }
+            }
+
+            /// Writes a field from JSON, failing when the value doesn't fit the field's type
+            pub fn set(
+                &mut self,
+                field: #enum_name,
+                value: ::serde_json::Value,
+            ) -> ::std::result::Result<(), ::serde_json::Error> {
+                match field {
+                    #(
+                        #enum_name::#variants => {
+                            self.#accesses = ::serde_json::from_value(value)?;
+                        }
+                    )*
+                }
+
+                ::std::result::Result::Ok(())
+            }
+        }
+    }
+}
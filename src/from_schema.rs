@@ -0,0 +1,588 @@
+use quote::format_ident;
+use serde_json::{Map, Value};
+
+use crate::naming;
+use crate::parser::{JsonMacroFlags, JsonValue};
+use crate::shape::{self, FieldShape, Shape, TaggedVariant};
+
+/// Reads the fields of the object a JSON Schema describes, as the same shapes that are inferred from samples.
+///
+/// - `type`, `properties` and `required` give the fields, fields that aren't required are optional
+/// - `items` gives the elements of arrays, and `additionalProperties` the values of objects without `properties`
+/// - `enum` of strings gives an enum, `format` gives sized numbers (`int32` → `i32`, `float` → `f32`)
+/// - `oneOf`/`anyOf` members are merged like samples, objects told apart by a `const` property
+///   become a tagged enum, and `null` members make the value optional
+/// - `allOf` objects are joined into one
+/// - local `$ref`s are followed, the objects and enums they point at are a [`Shape::Named`] after the
+///   definition, an object that refers to itself is a [`Shape::Recursive`],
+///   other types that refer to themselves are a `serde_json::Value`
+///
+/// # Parameters
+/// - `schema`: The schema document
+/// - `flags`: The macro flags, conflicting types follow `@untagged` and `@lenient`
+///
+/// # Returns
+/// The fields of the main struct, or an error message with the place in the schema that can't be read
+pub fn schema_fields(schema: &Value, flags: &JsonMacroFlags) -> Result<Vec<FieldShape>, String> {
+    let reader = SchemaReader {
+        root: schema,
+        flags,
+    };
+
+    // The document itself is being read, `"$ref": "#"` refers to the main struct
+    let mut refs = vec!["#"];
+
+    match reader.shape(schema, "#", &mut refs)? {
+        Shape::Object(fields) => Ok(fields),
+        _ => Err("the schema must describe an object with `properties`".to_string()),
+    }
+}
+
+/// Reads the shapes of the parts of a schema document
+struct SchemaReader<'a> {
+    root: &'a Value,
+    flags: &'a JsonMacroFlags,
+}
+
+impl<'a> SchemaReader<'a> {
+    /// Reads the shape of the values a schema accepts.
+    ///
+    /// # Parameters
+    /// - `schema`: The schema
+    /// - `path`: Where the schema is in the document, `#/properties/address`
+    /// - `refs`: The `$ref`s being followed, to find types that refer to themselves
+    fn shape(
+        &self,
+        schema: &'a Value,
+        path: &str,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<Shape, String> {
+        let object = match schema {
+            // `true` accepts anything, and `false` nothing
+            Value::Bool(_) => return Ok(Shape::Any),
+            Value::Object(object) => object,
+            _ => return Err(format!("`{}` isn't a schema", path)),
+        };
+
+        let shape = if let Some(reference) = object.get("$ref") {
+            self.reference(reference, path, refs)?
+        } else if let Some(members) = object.get("allOf") {
+            self.all_of(object, members, path, refs)?
+        } else if let Some(members) = object.get("oneOf") {
+            self.one_of(members, &format!("{}/oneOf", path), refs)?
+        } else if let Some(members) = object.get("anyOf") {
+            self.one_of(members, &format!("{}/anyOf", path), refs)?
+        } else if let Some(values) = object.get("enum") {
+            self.enum_shape(values, path)?
+        } else if let Some(value) = object.get("const") {
+            self.literal_shape(value, path)?
+        } else {
+            self.typed(object, path, refs)?
+        };
+
+        // `nullable` is how OpenAPI 3.0 allows `null`
+        if object.get("nullable") == Some(&Value::Bool(true)) {
+            Ok(nullable(shape))
+        } else {
+            Ok(shape)
+        }
+    }
+
+    /// Follows a local `$ref`, `#/$defs/Address`
+    fn reference(
+        &self,
+        reference: &'a Value,
+        path: &str,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<Shape, String> {
+        let Value::String(reference) = reference else {
+            return Err(format!("the `$ref` at `{}` isn't a string", path));
+        };
+
+        let pointer = reference.strip_prefix('#').ok_or_else(|| {
+            format!(
+                "only `$ref`s inside the schema are supported, `{}` at `{}`",
+                reference, path
+            )
+        })?;
+
+        let target = self
+            .root
+            .pointer(pointer)
+            .ok_or_else(|| format!("`{}` at `{}` doesn't exist", reference, path))?;
+
+        // An object holding itself refers to the struct generated for it, other types are kept as JSON
+        if refs.contains(&reference.as_str()) {
+            if self.is_struct(target) {
+                return Ok(Shape::Recursive(reference.clone()));
+            }
+
+            return Ok(Shape::Any);
+        }
+
+        refs.push(reference);
+        let shape = self.shape(target, reference, refs);
+        refs.pop();
+
+        Ok(named(reference, shape?))
+    }
+
+    /// Whether a schema is read as an object with fields, and nothing else, so it's generated as a struct
+    fn is_struct(&self, schema: &'a Value) -> bool {
+        let schema = self.resolve(schema);
+        let composed = ["allOf", "oneOf", "anyOf", "enum", "const"]
+            .iter()
+            .any(|keyword| schema.get(keyword).is_some());
+
+        let object = match schema.get("type") {
+            None => true,
+            Some(Value::String(name)) => name == "object",
+            Some(Value::Array(names)) => {
+                names.iter().all(|name| name == "object" || name == "null")
+            }
+            Some(_) => false,
+        };
+
+        !composed && object && schema.get("properties").is_some()
+    }
+
+    /// Joins the objects of an `allOf`, a field is required when any of them requires it
+    fn all_of(
+        &self,
+        object: &'a Map<String, Value>,
+        members: &'a Value,
+        path: &str,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<Shape, String> {
+        let members = members
+            .as_array()
+            .ok_or_else(|| format!("`{}/allOf` isn't an array", path))?;
+
+        let mut shapes = members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| self.shape(member, &format!("{}/allOf/{}", path, i), refs))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Properties next to the `allOf` are one more object
+        if object.contains_key("properties") {
+            shapes.push(self.typed(object, path, refs)?);
+        }
+
+        if shapes.len() == 1 {
+            return Ok(shapes.remove(0));
+        }
+
+        let mut fields: Vec<FieldShape> = Vec::new();
+
+        for shape in shapes {
+            let Shape::Object(member_fields) = unnamed(shape) else {
+                return Err(format!("`{}/allOf` can only join objects", path));
+            };
+
+            for field in member_fields {
+                match fields.iter_mut().find(|other| other.key == field.key) {
+                    Some(other) => {
+                        let shape = std::mem::replace(&mut other.shape, Shape::Unknown);
+                        other.shape = shape::merge(shape, field.shape, path, self.flags)?;
+                        other.missing &= field.missing;
+                    }
+                    None => fields.push(field),
+                }
+            }
+        }
+
+        Ok(Shape::Object(fields))
+    }
+
+    /// Merges the members of a `oneOf` or `anyOf`
+    ///
+    /// # Parameters
+    /// - `members`: The members
+    /// - `path`: Where the members are, `#/properties/payment/oneOf`
+    /// - `refs`: The `$ref`s being followed
+    fn one_of(
+        &self,
+        members: &'a Value,
+        path: &str,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<Shape, String> {
+        let members = members
+            .as_array()
+            .ok_or_else(|| format!("`{}` isn't an array", path))?;
+
+        let mut shapes = Vec::new();
+        let mut schemas = Vec::new();
+        let mut has_null = false;
+
+        for (i, member) in members.iter().enumerate() {
+            match self.shape(member, &format!("{}/{}", path, i), refs)? {
+                Shape::Null => has_null = true,
+                shape => {
+                    shapes.push(shape);
+                    schemas.push(self.resolve(member));
+                }
+            }
+        }
+
+        let shape = match self.tagged(&shapes, &schemas) {
+            Some(tagged) => tagged,
+            None => {
+                let mut merged = Shape::Unknown;
+                for shape in shapes {
+                    merged = shape::merge(merged, shape, path, self.flags)?;
+                }
+                merged
+            }
+        };
+
+        match shape {
+            Shape::Unknown if has_null => Ok(Shape::Null),
+            shape if has_null => Ok(nullable(shape)),
+            shape => Ok(shape),
+        }
+    }
+
+    /// Finds the property with a different `const` string in every object, it tells them apart.
+    ///
+    /// # Returns
+    /// The tagged shape, or `None` if some member isn't an object or there's no such property
+    fn tagged(&self, shapes: &[Shape], schemas: &[&'a Value]) -> Option<Shape> {
+        let tag_values = |schema: &'a Value| {
+            schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+                .filter_map(|(key, property)| {
+                    let property = self.resolve(property);
+                    let value = property
+                        .get("const")
+                        .or_else(|| match property.get("enum") {
+                            Some(Value::Array(values)) if values.len() == 1 => values.first(),
+                            _ => None,
+                        });
+
+                    Some((key.as_str(), value?.as_str()?))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        if shapes.len() < 2 {
+            return None;
+        }
+
+        let candidates = tag_values(schemas.first()?);
+
+        let (tag, tag_values) = candidates.iter().find_map(|(tag, _)| {
+            let values = schemas
+                .iter()
+                .map(|schema| {
+                    tag_values(schema)
+                        .into_iter()
+                        .find_map(|(key, value)| (key == *tag).then_some(value))
+                })
+                .collect::<Option<Vec<_>>>()?;
+
+            // Every object needs its own value
+            let unique = values
+                .iter()
+                .enumerate()
+                .all(|(i, value)| !values[..i].contains(value));
+
+            unique.then_some((*tag, values))
+        })?;
+
+        let mut variants: Vec<TaggedVariant> = Vec::new();
+        for (shape, tag_value) in shapes.iter().zip(tag_values) {
+            let Shape::Object(fields) = shape.resolved() else {
+                return None;
+            };
+
+            let fields = fields
+                .iter()
+                .filter(|field| field.key != tag)
+                .cloned()
+                .collect();
+
+            variants.push(TaggedVariant::new(tag_value.to_string(), fields, &variants));
+        }
+
+        Some(Shape::Tagged {
+            tag: tag.to_string(),
+            variants,
+        })
+    }
+
+    /// Follows `$ref`s to the schema they point at, for reading its keywords
+    fn resolve(&self, schema: &'a Value) -> &'a Value {
+        let mut schema = schema;
+
+        // Bounded, in case `$ref`s point at each other
+        for _ in 0..32 {
+            match schema
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix('#'))
+                .and_then(|pointer| self.root.pointer(pointer))
+            {
+                Some(target) => schema = target,
+                None => break,
+            }
+        }
+
+        schema
+    }
+
+    /// An `enum` of strings is an enum, other values are merged like the elements of an array
+    fn enum_shape(&self, values: &Value, path: &str) -> Result<Shape, String> {
+        let values = values
+            .as_array()
+            .ok_or_else(|| format!("`{}/enum` isn't an array", path))?;
+
+        let has_null = values.contains(&Value::Null);
+        let values = values
+            .iter()
+            .filter(|value| !value.is_null())
+            .collect::<Vec<_>>();
+
+        let strings = values
+            .iter()
+            .map(|value| value.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>();
+
+        let shape = match strings {
+            Some(strings) if !strings.is_empty() => Shape::Enum(strings),
+            _ => {
+                let mut merged = Shape::Unknown;
+                for value in values {
+                    merged =
+                        shape::merge(merged, self.literal_shape(value, path)?, path, self.flags)?;
+                }
+                merged
+            }
+        };
+
+        match shape {
+            Shape::Unknown if has_null => Ok(Shape::Null),
+            shape if has_null => Ok(nullable(shape)),
+            shape => Ok(shape),
+        }
+    }
+
+    /// The shape of a value written in the schema, the same as in a sample
+    fn literal_shape(&self, value: &Value, path: &str) -> Result<Shape, String> {
+        shape::infer(&JsonValue::from_serde_value(value), path, self.flags)
+    }
+
+    /// Reads `type`, which can list several types, `["string", "null"]`
+    fn typed(
+        &self,
+        object: &'a Map<String, Value>,
+        path: &str,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<Shape, String> {
+        let types = match object.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names
+                .iter()
+                .map(|name| {
+                    name.as_str()
+                        .ok_or_else(|| format!("`{}/type` lists something that isn't a type", path))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err(format!("`{}/type` isn't a string or an array", path)),
+
+            // Without a type, the keywords tell what the value is
+            None if object.contains_key("properties")
+                || object.contains_key("additionalProperties") =>
+            {
+                vec!["object"]
+            }
+            None if object.contains_key("items") => vec!["array"],
+            None => return Ok(Shape::Any),
+        };
+
+        let has_null = types.contains(&"null");
+        let mut shape = Shape::Unknown;
+
+        for name in types.into_iter().filter(|name| *name != "null") {
+            let single = self.single_type(name, object, path, refs)?;
+            shape = shape::merge(shape, single, path, self.flags)?;
+        }
+
+        match shape {
+            Shape::Unknown if has_null => Ok(Shape::Null),
+            shape if has_null => Ok(nullable(shape)),
+            shape => Ok(shape),
+        }
+    }
+
+    /// Reads a single `type`, with the keywords that go with it
+    fn single_type(
+        &self,
+        name: &str,
+        object: &'a Map<String, Value>,
+        path: &str,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<Shape, String> {
+        let format = object.get("format").and_then(Value::as_str);
+
+        let shape = match name {
+            "string" => Shape::Str,
+            "boolean" => Shape::Bool,
+
+            // `int32` and `uint8` are sized integers, otherwise the type has to hold the bounds
+            "integer" => match format.and_then(integer_format) {
+                Some(int_type) => explicit(int_type),
+                None => {
+                    let bound = |keyword: &str| {
+                        object.get(keyword).and_then(|bound| {
+                            bound
+                                .as_i64()
+                                .map(i128::from)
+                                .or_else(|| bound.as_u64().map(i128::from))
+                        })
+                    };
+
+                    let min = bound("minimum").unwrap_or(0);
+                    let max = bound("maximum").unwrap_or(0);
+
+                    shape::int(min.min(max), max.max(min), path, self.flags)?
+                }
+            },
+            "number" => match format {
+                Some("float") => explicit("f32"),
+                _ => Shape::Float,
+            },
+
+            "array" => match object.get("items") {
+                Some(items) => Shape::Array(Box::new(self.shape(
+                    items,
+                    &format!("{}/items", path),
+                    refs,
+                )?)),
+                None => Shape::Array(Box::new(Shape::Unknown)),
+            },
+
+            // Objects without properties are maps, `additionalProperties` gives their values
+            "object" => match object.get("properties") {
+                Some(properties) => Shape::Object(self.fields(object, properties, path, refs)?),
+                None => match object.get("additionalProperties") {
+                    Some(Value::Bool(false)) => Shape::Object(Vec::new()),
+                    Some(values) => Shape::Map(Box::new(self.shape(
+                        values,
+                        &format!("{}/additionalProperties", path),
+                        refs,
+                    )?)),
+                    None => Shape::Map(Box::new(Shape::Any)),
+                },
+            },
+
+            _ => return Err(format!("`{}` has an unknown type `{}`", path, name)),
+        };
+
+        Ok(shape)
+    }
+
+    /// Reads the fields of an object, in the order of its `properties`
+    fn fields(
+        &self,
+        object: &'a Map<String, Value>,
+        properties: &'a Value,
+        path: &str,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<Vec<FieldShape>, String> {
+        let properties = properties
+            .as_object()
+            .ok_or_else(|| format!("`{}/properties` isn't an object", path))?;
+
+        let required = object
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| {
+                required
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        properties
+            .iter()
+            .map(|(key, property)| {
+                // `~` and `/` are escaped in JSON pointers
+                let property_path = format!(
+                    "{}/properties/{}",
+                    path,
+                    key.replace('~', "~0").replace('/', "~1")
+                );
+
+                Ok(FieldShape {
+                    key: key.clone(),
+                    shape: self.shape(property, &property_path, refs)?,
+                    missing: !required.contains(&key.as_str()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Also accepts `null`
+fn nullable(shape: Shape) -> Shape {
+    match shape {
+        Shape::Null | Shape::Optional(_) | Shape::Any => shape,
+        shape => Shape::Optional(Box::new(shape)),
+    }
+}
+
+/// The type a `$ref` points at, objects and enums are named after the last part of the reference,
+/// `#/$defs/Address` → `Address`, other types are used where they're referred to
+fn named(reference: &str, shape: Shape) -> Shape {
+    match shape {
+        Shape::Optional(inner) => Shape::Optional(Box::new(named(reference, *inner))),
+        Shape::Object(_) | Shape::Enum(_) | Shape::Union(_) | Shape::Tagged { .. } => {
+            // `~1` and `~0` are `/` and `~` in JSON pointers
+            let key = reference.rsplit('/').next().unwrap_or(reference);
+            let key = key.replace("~1", "/").replace("~0", "~");
+
+            Shape::Named {
+                reference: reference.to_string(),
+                name: naming::type_name(&key, false),
+                shape: Box::new(shape),
+            }
+        }
+        shape => shape,
+    }
+}
+
+/// The shape a [`Shape::Named`] stands for, other shapes are kept
+fn unnamed(shape: Shape) -> Shape {
+    match shape {
+        Shape::Named { shape, .. } => *shape,
+        shape => shape,
+    }
+}
+
+/// A type used verbatim, like the ones given with `as Type`
+fn explicit(type_name: &str) -> Shape {
+    let type_name = format_ident!("{}", type_name);
+    Shape::Explicit(Box::new(syn::parse_quote!(#type_name)))
+}
+
+/// The integer type of a `format`, `int32` → `i32`, `uint64` → `u64`
+fn integer_format(format: &str) -> Option<&'static str> {
+    let int_type = match format {
+        "int8" => "i8",
+        "int16" => "i16",
+        "int32" => "i32",
+        "int64" => "i64",
+        "uint8" => "u8",
+        "uint16" => "u16",
+        "uint32" => "u32",
+        "uint64" => "u64",
+        _ => return None,
+    };
+
+    Some(int_type)
+}
@@ -1,8 +1,49 @@
-use inflections::Inflect;
+use std::collections::{HashMap, HashSet};
+
 use quote::{format_ident, quote, ToTokens};
-use syn::Ident;
+use syn::{ext::IdentExt, Ident};
+
+use crate::builder::{self, BuilderField};
+use crate::dedupe;
+use crate::fields::{self, AccessorField};
+use crate::naming::{self, Names};
+use crate::parser::{integer_range, JsonMacroFlags, JsonMacroInput, JsonStruct, RenameStyle};
+use crate::sample;
+use crate::schema;
+use crate::shape::{self, FieldShape, Shape, TaggedVariant};
 
-use crate::parser::{JsonMacroInput, JsonStruct, JsonValue};
+/// State shared by every type generated for one macro invocation
+struct Context<'a> {
+    flags: &'a JsonMacroFlags,
+    /// Signatures of the nested objects that share one struct, with their name if it's known up front
+    shared: HashMap<String, Option<Ident>>,
+    /// Names of the shared structs already generated, by signature
+    generated: HashMap<String, Ident>,
+    /// Every type name taken so far
+    names: Names,
+    /// The struct whose fields are being generated, used to disambiguate nested names
+    parent: Ident,
+    /// Names of the types generated for schema definitions, by `$ref`, `#` being the main struct
+    definitions: HashMap<String, Ident>,
+    /// Names of the generated structs and enums, by the path of the values they hold
+    types: HashMap<String, Ident>,
+    /// Every generated struct, in the order they were generated
+    structs: Vec<GeneratedStruct>,
+    /// The error returned by the builders, with `@builder`
+    build_error: Option<Ident>,
+    /// The builder of every generated struct, by struct name, with `@builder`
+    builders: HashMap<Ident, Ident>,
+    /// The fields reachable from every generated struct, by struct name, with `@fields`
+    accessors: HashMap<Ident, Vec<AccessorField>>,
+}
+
+/// A generated struct, used to build values of it for `@sample` and `@default_from_sample`
+pub struct GeneratedStruct {
+    pub name: Ident,
+    /// Where the struct was generated, the types nested in it are found from there
+    pub path: String,
+    pub fields: Vec<FieldShape>,
+}
 
 /// Generates Rust structs from a JSON-like structure with flexible configuration.
 ///
@@ -14,84 +55,245 @@ use crate::parser::{JsonMacroInput, JsonStruct, JsonValue};
 /// A tuple containing:
 /// 1. The main generated struct as a token stream
 /// 2. A vector of additional nested structs
+///
+/// # Errors
+/// If values at the same place have conflicting types and `@lenient` isn't set
 pub fn generate_structs(
     json_struct: &JsonMacroInput,
     base_name: &Ident,
+) -> syn::Result<(proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>)> {
+    // Infer the shape of every field, merging array elements and every sample together
+    let samples = std::iter::once(&json_struct.content)
+        .chain(&json_struct.samples)
+        .collect::<Vec<_>>();
+
+    let fields = shape::infer_samples(samples.iter().copied(), &json_struct.flags)
+        .map_err(|message| syn::Error::new(base_name.span(), message))?;
+
+    generate_types(&fields, base_name, &samples, &json_struct.flags)
+}
+
+/// Generates the main struct and every type nested in it from the shapes of its fields,
+/// whether they were inferred from samples or read from a JSON Schema.
+///
+/// # Parameters
+/// - `fields`: The fields of the main struct
+/// - `base_name`: The base name for the primary struct
+/// - `samples`: The samples the fields were inferred from, none for a schema
+/// - `flags`: The macro flags
+///
+/// # Returns
+/// A tuple containing:
+/// 1. The main generated struct as a token stream
+/// 2. A vector of additional nested structs
+///
+/// # Errors
+/// If a `@share(...)` name is already used, or a sample can't be built as its type
+pub fn generate_types(
+    fields: &[FieldShape],
+    base_name: &Ident,
+    samples: &[&JsonStruct],
+    flags: &JsonMacroFlags,
+) -> syn::Result<(proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>)> {
+    // Find the nested objects that are identical, so they share one struct
+    let mut names = Names::new(base_name);
+    let shared = dedupe::shared_types(fields, base_name, flags, &mut names)?;
+
+    let mut ctx = Context {
+        flags,
+        shared,
+        generated: HashMap::new(),
+        names,
+        parent: base_name.clone(),
+        definitions: HashMap::from([("#".to_string(), base_name.clone())]),
+        types: HashMap::from([(String::new(), base_name.clone())]),
+        structs: Vec::new(),
+        build_error: None,
+        builders: HashMap::new(),
+        accessors: HashMap::new(),
+    };
+
+    // Every builder returns the same error, `UserBuildError`
+    let mut error_impl = None;
+    if ctx.flags.builder {
+        let error_name = ctx
+            .names
+            .claim(&format_ident!("{}BuildError", base_name), base_name);
+
+        error_impl = Some(builder::build_error(&error_name, ctx.flags));
+        ctx.build_error = Some(error_name);
+    }
+
+    let (main_struct, mut nested_structs) = generate_struct(fields, base_name, "", &mut ctx);
+
+    nested_structs.extend(error_impl);
+
+    // `@sample` builds the first sample as a struct literal
+    if ctx.flags.sample {
+        if let Some(content) = samples.first() {
+            nested_structs.push(sample::sample_impl(
+                base_name,
+                content,
+                &ctx.types,
+                &ctx.structs,
+                ctx.flags,
+            )?);
+        }
+    }
+
+    // `@default_from_sample` builds the first value seen for every struct
+    if ctx.flags.default_from_sample {
+        nested_structs.extend(sample::default_impls(
+            base_name,
+            samples,
+            &ctx.types,
+            &ctx.structs,
+            ctx.flags,
+        )?);
+    }
+
+    // `@json_schema` describes the main struct, with the samples as examples
+    if ctx.flags.json_schema {
+        nested_structs.push(schema::schema_impl(
+            base_name,
+            samples,
+            &ctx.types,
+            &ctx.structs,
+            ctx.flags,
+        )?);
+    }
+
+    Ok((main_struct, nested_structs))
+}
+
+/// Generates a struct, and every struct nested inside it, from the shapes of its fields.
+///
+/// # Parameters
+/// - `fields`: The inferred fields of the struct
+/// - `base_name`: The name of the struct, nested struct names are built from it
+/// - `path`: Where the struct's values are, see [`field_path`]
+/// - `ctx`: The generation context
+///
+/// # Returns
+/// A tuple containing:
+/// 1. The generated struct as a token stream
+/// 2. A vector of additional nested structs
+fn generate_struct(
+    fields: &[FieldShape],
+    base_name: &Ident,
+    path: &str,
+    ctx: &mut Context,
 ) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    ctx.structs.push(GeneratedStruct {
+        name: base_name.clone(),
+        path: path.to_string(),
+        fields: fields.to_vec(),
+    });
+
+    // The builder and field enum names are claimed before the nested types, so `details_builder` can't take `DetailsBuilder`
+    let builder_name = ctx.build_error.is_some().then(|| {
+        let builder_name = ctx
+            .names
+            .claim(&builder::builder_type_name(base_name), base_name);
+        ctx.builders.insert(base_name.clone(), builder_name.clone());
+        builder_name
+    });
+    let field_enum_name = ctx.flags.fields.then(|| {
+        ctx.names
+            .claim(&fields::field_enum_name(base_name), base_name)
+    });
+
     // Collect all generated structs
     let mut all_structs = Vec::new();
-    let mut fields = Vec::new();
+    let mut struct_fields = Vec::new();
+    let mut builder_fields = Vec::new();
+    let mut accessor_fields = Vec::new();
 
-    // Determine base derives
-    //
-    // usually clone is needed for json, so by default, it get's derived
-    let mut derives = vec![quote!(::std::clone::Clone)];
+    let derives = derives(ctx.flags);
 
-    // Conditionally add derives based on flags
-    //
-    // not really need to be a seprate flag, but it's nice to have a quick way to do so
-    if json_struct.flags.debug {
-        derives.push(quote!(::std::fmt::Debug));
-    }
+    // Nested names are disambiguated with the name of this struct
+    let parent = std::mem::replace(&mut ctx.parent, base_name.clone());
 
-    // Collected from the `@derive(...)`
-    derives.extend(json_struct.flags.custom_derives.iter().map(|d| quote!(#d)));
+    // Keys aren't always valid identifiers, `userId` → `user_id`, `type` → `r#type`
+    let mut taken = HashSet::new();
+    let field_names = fields
+        .iter()
+        .map(|field| {
+            (
+                naming::field_ident(&field.key, &mut taken),
+                field.key.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // The styles in the struct's `rename_all`, without a style flag they're detected from the keys,
+    // so `{"userId": 1}` gives `user_id` with `rename_all = "camelCase"`
+    let (serialize_style, deserialize_style) =
+        match (&ctx.flags.rename_serialize, &ctx.flags.rename_deserialize) {
+            (None, None) => {
+                let detected = naming::detect_key_style(&field_names);
+                (detected.clone(), detected)
+            }
+            (serialize, deserialize) => (serialize.clone(), deserialize.clone()),
+        };
+
+    // Process each field of the struct
+    for (field, (field_name, key)) in fields.iter().zip(&field_names) {
+        // Nested structs are named after the key, singular for the elements of arrays
+        //
+        // `Example`
+        //
+        //```rust
+        //
+        // struct Company {
+        //  employees: Vec<Employee>
+        // }
+        //
+        // struct Employee;
+        //
+        //````
+        //
+        // with `@prefix_nested` the name of the struct is prepended, `CompanyEmployee`
+        let is_array = match &field.shape {
+            Shape::Array(_) | Shape::Map(_) => true,
+            Shape::Optional(inner) => matches!(inner.as_ref(), Shape::Array(_) | Shape::Map(_)),
+            _ => false,
+        };
 
-    // Process each entry in the JSON-like structure
-    for (key, value) in &json_struct.content.entries {
-        // Just in case the identifier is not a valid struct name
-        let field_name = format_ident!("{}", sanitize_identifier(key));
+        let nested_name = naming::nested_type_name(base_name, key, is_array, ctx.flags);
 
         // Infer field type and handle nested structures
-        let (field_type, _) = match value {
-            JsonValue::Str(_) => (quote!(String), Vec::<proc_macro2::TokenStream>::new()),
-            JsonValue::Number(_) => (quote!(f64), Vec::new()),
-            JsonValue::Boolean(_) => (quote!(bool), Vec::new()),
-
-            JsonValue::Array(arr) => {
-                let (elem_type, _) = infer_array_type(arr);
-                (quote!(Vec<#elem_type>), Vec::new())
-            }
+        let field_path = field_path(path, key);
+        //
+        // optional fields are generated as the type of their values, then wrapped in an `Option`
+        let (value_type, nested_structs) = match &field.shape {
+            // Nothing but `null` was seen, so the type isn't known
+            Shape::Null => (quote!(::serde_json::Value), Vec::new()),
+            Shape::Optional(inner) => shape_type(inner, &nested_name, &field_path, ctx),
+            shape => shape_type(shape, &nested_name, &field_path, ctx),
+        };
 
-            JsonValue::Object(obj) => {
-                // Generate nested struct for object and concat the key with the struct name
-                //
-                // `Example`
-                //
-                //```rust
-                //
-                // struct User {
-                //  age: UserAge
-                // }
-                //
-                // struct UserAge;
-                //
-                //````
-                let nested_name = format_ident!("{}{}", base_name, key.to_pascal_case());
-
-                let json_content = JsonStruct {
-                    entries: obj.clone(),
-                };
-
-                let nested_macro_input = JsonMacroInput {
-                    struct_name: json_struct.struct_name.clone(),
-                    flags: json_struct.flags.clone(),
-                    content: json_content,
-                };
-
-                // Recursively generate nested structs
-                let (nested_struct, nested_structs) =
-                    generate_structs(&nested_macro_input, &nested_name);
-
-                all_structs.extend(nested_structs);
-                all_structs.push(nested_struct.clone());
-
-                (
-                    format_ident!("{}", nested_name).into_token_stream(),
-                    Vec::new(),
-                )
-            }
-            JsonValue::Null => (quote!(Option<::serde_json::Value>), Vec::new()),
+        all_structs.extend(nested_structs);
+
+        let mut attrs = Vec::new();
+
+        // Fields missing from some of the objects, or that are sometimes `null`, are optional
+        let optional_attr =
+            quote!(#[serde(default, skip_serializing_if = "::std::option::Option::is_none")]);
+
+        let optional = field.missing || matches!(field.shape, Shape::Null | Shape::Optional(_));
+
+        match field.shape {
+            Shape::Null if field.missing => attrs.push(quote!(#[serde(default)])),
+            Shape::Null => {}
+            _ if optional => attrs.push(optional_attr),
+            _ => {}
+        }
+
+        let field_type = if optional {
+            quote!(::std::option::Option<#value_type>)
+        } else {
+            value_type.clone()
         };
 
         // Handle Serde alias configuration
@@ -121,87 +323,668 @@ pub fn generate_structs(
         //
         // this will only deserialize if you give it a camelCase keys, not snake_case
         //
-        // this is where the `#[serde(alias = "jobs_list")]` comes in, it allows you to have both,
-        // so you can deserialize with camelCase and snake_case
-        let field = if json_struct.flags.use_serde_alias {
-            quote! {
-                #[serde(alias = #key)]
-                #field_name: #field_type
-            }
-        } else {
-            quote! {
-                #field_name: #field_type
+        // this is where `@alias` comes in, it serializes to camelCase and deserializes from both,
+        //
+        // ```rust
+        // #[serde(rename(serialize = "jobsList", deserialize = "jobs_list"), alias = "jobsList")]
+        // jobs_list: Vec<String>
+        // ```
+        //
+        // without a rename style the key is simply kept when the field name differs from it
+        attrs.extend(rename_attr(
+            field_name,
+            key,
+            (&serialize_style, &deserialize_style),
+            ctx.flags,
+        ));
+
+        // Objects, maybe optional, get a closure-based setter in the builder
+        let nested = match field.shape.resolved() {
+            Shape::Object(_) => ctx.types.get(&field_path).cloned(),
+            Shape::Optional(inner) if matches!(inner.resolved(), Shape::Object(_)) => {
+                ctx.types.get(&field_path).cloned()
             }
+            _ => None,
         };
+        let nested_builder = nested.as_ref().and_then(|nested| {
+            let builder_name = ctx.builders.get(nested)?;
+            Some((nested.clone(), builder_name.clone()))
+        });
+
+        // The fields of nested structs are reachable too, unless they can be missing
+        if ctx.flags.fields {
+            let unraw = field_name.unraw().to_string();
+            let variant = format_ident!("{}", naming::type_name(&unraw, false));
+            let wire_name = serialized_name(&unraw, key, ctx.flags);
+            let accessor = AccessorField::new(variant, field_name, wire_name);
+
+            let children = match (field.shape.resolved(), &nested) {
+                (Shape::Object(_), Some(nested)) if !optional => ctx.accessors.get(nested),
+                _ => None,
+            };
 
-        fields.push(field);
+            let children = children
+                .into_iter()
+                .flatten()
+                .map(|child| accessor.nested(child))
+                .collect::<Vec<_>>();
+
+            accessor_fields.push(accessor);
+            accessor_fields.extend(children);
+        }
+
+        builder_fields.push(BuilderField {
+            name: field_name.clone(),
+            ty: value_type,
+            optional,
+            nested: nested_builder,
+        });
+
+        let field_vis = &ctx.flags.field_vis;
+
+        struct_fields.push(quote! {
+            #(#attrs)*
+            #field_vis #field_name: #field_type
+        });
     }
 
+    ctx.parent = parent;
+
     // Prepare struct name and rename strategy
     let struct_name = base_name;
-    let style = json_struct
-        .clone()
-        .flags
-        .rename_all
-        .map(|style| Some(style.to_string()));
+    let style = match (&serialize_style, &deserialize_style) {
+        (None, None) => None,
+        (Some(serialize), Some(deserialize)) if serialize == deserialize => {
+            let style = serialize.to_string();
+            Some(quote!(rename_all = #style))
+        }
+        (serialize, deserialize) => {
+            let serialize = serialize.iter().map(|style| {
+                let style = style.to_string();
+                quote!(serialize = #style)
+            });
+            let deserialize = deserialize.iter().map(|style| {
+                let style = style.to_string();
+                quote!(deserialize = #style)
+            });
+
+            Some(quote!(rename_all(#(#serialize,)* #(#deserialize),*)))
+        }
+    };
+
+    let mut struct_attrs = Vec::new();
+
+    if let Some(rename_all_style) = style {
+        struct_attrs.push(quote!(#[serde(#rename_all_style)]));
+    }
+
+    // Missing fields take their value from the `Default` impl built from the sample
+    if ctx.flags.default_from_sample {
+        struct_attrs.push(quote!(#[serde(default)]));
+    }
+
+    // Nested structs get the same visibility as the main one
+    let vis = &ctx.flags.vis;
 
     // Generate the main struct with optional rename strategy
-    let main_struct = if let Some(rename_all_style) = style {
-        quote! {
-            #[derive(#(#derives),*, ::serde::Deserialize, ::serde::Serialize)]
-            #[serde(rename_all = #rename_all_style)]
-            struct #struct_name {
-                #(#fields),*
-            }
+    let main_struct = quote! {
+        #[derive(#(#derives),*, ::serde::Deserialize, ::serde::Serialize)]
+        #(#struct_attrs)*
+        #vis struct #struct_name {
+            #(#struct_fields),*
         }
-    } else {
+    };
+
+    if let (Some(builder_name), Some(error_name)) = (&builder_name, &ctx.build_error) {
+        all_structs.push(builder::builder_impl(
+            struct_name,
+            builder_name,
+            &builder_fields,
+            error_name,
+            ctx.flags,
+        ));
+    }
+
+    if let Some(field_enum_name) = &field_enum_name {
+        fields::unique_variants(&mut accessor_fields);
+        all_structs.push(fields::fields_impl(
+            struct_name,
+            field_enum_name,
+            &accessor_fields,
+            ctx.flags,
+        ));
+        ctx.accessors.insert(struct_name.clone(), accessor_fields);
+    }
+
+    (main_struct, all_structs)
+}
+
+/// Generates an untagged enum for values of conflicting types, with one variant per kind of value.
+///
+/// `Example`
+///
+/// ```rust,ignore
+/// #[serde(untagged)]
+/// enum Tag {
+///     Str(String),
+///     Num(f64),
+///     Obj(TagObj),
+/// }
+/// ```
+///
+/// # Parameters
+/// - `members`: The shapes of the variants
+/// - `enum_name`: The wanted name of the enum, types generated for the variants are named after it
+/// - `path`: Where the enum's values are, see [`field_path`]
+/// - `ctx`: The generation context
+///
+/// # Returns
+/// A tuple containing:
+/// 1. The enum's name as a type
+/// 2. The enum followed by every struct generated for it
+fn generate_enum(
+    members: &[Shape],
+    enum_name: &Ident,
+    path: &str,
+    ctx: &mut Context,
+) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    let enum_name = ctx.names.claim(enum_name, &ctx.parent);
+    ctx.types.insert(path.to_string(), enum_name.clone());
+    let member_base = &enum_name;
+
+    let mut all_structs = Vec::new();
+    let mut variants = Vec::new();
+
+    // Serde tries the variants in order and structs also deserialize from sequences,
+    // so arrays have to come before objects
+    let mut members = members.iter().collect::<Vec<_>>();
+    members.sort_by_key(|member| matches!(member.resolved(), Shape::Object(_) | Shape::Map(_)));
+
+    for member in members {
+        let (variant_name, nested_name) = match member.resolved() {
+            Shape::Str => ("Str", member_base.clone()),
+            Shape::Enum(_) => ("Str", format_ident!("{}Str", member_base)),
+            Shape::Map(_) => ("Map", format_ident!("{}Map", member_base)),
+            Shape::Int { .. } | Shape::Float => ("Num", member_base.clone()),
+            Shape::Bool => ("Bool", member_base.clone()),
+            Shape::Object(_) => ("Obj", format_ident!("{}Obj", member_base)),
+            Shape::Array(_) => ("Arr", format_ident!("{}Arr", member_base)),
+            _ => ("Other", member_base.clone()),
+        };
+
+        let variant_name = format_ident!("{}", variant_name);
+        let member_path = variant_path(path, &variant_name.to_string());
+        let (variant_type, nested_structs) = shape_type(member, &nested_name, &member_path, ctx);
+
+        all_structs.extend(nested_structs);
+        variants.push(quote!(#variant_name(#variant_type)));
+    }
+
+    let derives = derives(ctx.flags);
+    let vis = &ctx.flags.vis;
+
+    all_structs.push(quote! {
+        #[derive(#(#derives),*, ::serde::Deserialize, ::serde::Serialize)]
+        #[serde(untagged)]
+        #vis enum #enum_name {
+            #(#variants),*
+        }
+    });
+
+    (enum_name.into_token_stream(), all_structs)
+}
+
+/// Generates an internally tagged enum, with one variant per tag value.
+///
+/// `Example`
+///
+/// ```rust,ignore
+/// #[serde(tag = "type")]
+/// enum Event {
+///     #[serde(rename = "click")]
+///     Click(EventClick),
+///     #[serde(rename = "page_view")]
+///     PageView(EventPageView),
+/// }
+/// ```
+///
+/// # Parameters
+/// - `tag`: The name of the tag field
+/// - `variants`: The variants, with the fields of each one
+/// - `enum_name`: The wanted name of the enum, variant struct names are built from it
+/// - `path`: Where the enum's values are, see [`field_path`]
+/// - `ctx`: The generation context
+///
+/// # Returns
+/// A tuple containing:
+/// 1. The enum's name as a type
+/// 2. The enum followed by every struct generated for it
+fn generate_tagged_enum(
+    tag: &str,
+    variants: &[TaggedVariant],
+    enum_name: &Ident,
+    path: &str,
+    ctx: &mut Context,
+) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    let enum_name = ctx.names.claim(enum_name, &ctx.parent);
+    ctx.types.insert(path.to_string(), enum_name.clone());
+
+    let mut all_structs = Vec::new();
+    let mut enum_variants = Vec::new();
+
+    for variant in variants {
+        let tag_value = &variant.tag_value;
+        let variant_name = format_ident!("{}", variant.name);
+        let variant_struct = ctx
+            .names
+            .claim(&format_ident!("{}{}", enum_name, variant_name), &enum_name);
+
+        let variant_path = variant_path(path, tag_value);
+        ctx.types
+            .insert(variant_path.clone(), variant_struct.clone());
+
+        let (nested_struct, nested_structs) =
+            generate_struct(&variant.fields, &variant_struct, &variant_path, ctx);
+
+        all_structs.extend(nested_structs);
+        all_structs.push(nested_struct);
+
+        enum_variants.push(quote! {
+            #[serde(rename = #tag_value)]
+            #variant_name(#variant_struct)
+        });
+    }
+
+    let derives = derives(ctx.flags);
+    let vis = &ctx.flags.vis;
+
+    all_structs.push(quote! {
+        #[derive(#(#derives),*, ::serde::Deserialize, ::serde::Serialize)]
+        #[serde(tag = #tag)]
+        #vis enum #enum_name {
+            #(#enum_variants),*
+        }
+    });
+
+    (enum_name.into_token_stream(), all_structs)
+}
+
+/// Generates an enum for a fixed set of strings, with one unit variant per value.
+///
+/// `Example`
+///
+/// ```rust,ignore
+/// enum Status {
+///     #[serde(rename = "in_progress")]
+///     InProgress,
+///     #[serde(rename = "done")]
+///     Done,
+/// }
+/// ```
+///
+/// # Parameters
+/// - `values`: The strings
+/// - `enum_name`: The wanted name of the enum
+/// - `path`: Where the enum's values are, see [`field_path`]
+/// - `ctx`: The generation context
+///
+/// # Returns
+/// A tuple containing:
+/// 1. The enum's name as a type
+/// 2. The enum
+fn generate_string_enum(
+    values: &[String],
+    enum_name: &Ident,
+    path: &str,
+    ctx: &mut Context,
+) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    let enum_name = ctx.names.claim(enum_name, &ctx.parent);
+    ctx.types.insert(path.to_string(), enum_name.clone());
+
+    // Values can give the same name, `in-progress` and `in_progress`
+    let mut taken = HashSet::new();
+    let variants = values.iter().map(|value| {
+        let name = naming::type_name(value, false);
+        let name = (1..)
+            .map(|n| match n {
+                1 => name.clone(),
+                n => format!("{}{}", name, n),
+            })
+            .find(|name| !taken.contains(name))
+            .unwrap_or_default();
+
+        taken.insert(name.clone());
+        let variant_name = format_ident!("{}", name);
+
         quote! {
-            #[derive(#(#derives),*, ::serde::Deserialize, ::serde::Serialize)]
-            struct #struct_name {
-                #(#fields),*
-            }
+            #[serde(rename = #value)]
+            #variant_name
+        }
+    });
+
+    let derives = derives(ctx.flags);
+    let vis = &ctx.flags.vis;
+
+    let string_enum = quote! {
+        #[derive(#(#derives),*, ::serde::Deserialize, ::serde::Serialize)]
+        #vis enum #enum_name {
+            #(#variants),*
         }
     };
 
-    (main_struct, all_structs)
+    (enum_name.into_token_stream(), vec![string_enum])
 }
 
-/// Infers the element type for an array of JSON values.
+/// Maps an inferred shape to a Rust type, generating structs for objects.
 ///
 /// # Parameters
-/// - `arr`: A slice of JSON values
+/// - `shape`: The inferred shape
+/// - `nested_name`: The wanted name of the type generated when the shape is an object or an enum
+/// - `path`: Where the values are, see [`field_path`]
+/// - `ctx`: The generation context
 ///
 /// # Returns
 /// A tuple containing:
-/// 1. The inferred element type as a token stream
-/// 2. Any additional generated structs (currently unused)
-fn infer_array_type(
-    arr: &[JsonValue],
+/// 1. The type as a token stream
+/// 2. Any additional generated structs
+fn shape_type(
+    shape: &Shape,
+    nested_name: &Ident,
+    path: &str,
+    ctx: &mut Context,
+) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    match shape {
+        Shape::Str => (quote!(::std::string::String), Vec::new()),
+        Shape::Int { min, max } => (
+            integer_type(*min, *max, ctx.flags).expect("integer shapes always have a type"),
+            Vec::new(),
+        ),
+        Shape::Float => (quote!(f64), Vec::new()),
+        Shape::Bool => (quote!(bool), Vec::new()),
+
+        // Nested arrays share the element type, `[[{...}]]` is `Vec<Vec<Element>>`,
+        // and mixed elements get an enum, `tags: Vec<Tag>`
+        Shape::Array(elem) => {
+            let (elem_type, elem_structs) = match elem.as_ref() {
+                Shape::Recursive(reference) => recursive_type(reference, &element_path(path), ctx),
+                elem => shape_type(elem, nested_name, &element_path(path), ctx),
+            };
+            (quote!(::std::vec::Vec<#elem_type>), elem_structs)
+        }
+
+        Shape::Object(fields) => {
+            let signature = dedupe::signature(shape, ctx.flags);
+
+            // Identical objects share the struct generated for the first one
+            if let Some(name) = ctx.generated.get(&signature) {
+                ctx.types.insert(path.to_string(), name.clone());
+                return (name.into_token_stream(), Vec::new());
+            }
+
+            let nested_name = match ctx.shared.get(&signature).cloned() {
+                Some(shared_name) => {
+                    let name =
+                        shared_name.unwrap_or_else(|| ctx.names.claim(nested_name, &ctx.parent));
+                    ctx.generated.insert(signature, name.clone());
+                    name
+                }
+                None => ctx.names.claim(nested_name, &ctx.parent),
+            };
+
+            ctx.types.insert(path.to_string(), nested_name.clone());
+
+            // Recursively generate nested structs
+            let (nested_struct, mut nested_structs) =
+                generate_struct(fields, &nested_name, path, ctx);
+
+            nested_structs.push(nested_struct);
+
+            (nested_name.into_token_stream(), nested_structs)
+        }
+
+        Shape::Optional(inner) => {
+            let (inner_type, inner_structs) = shape_type(inner, nested_name, path, ctx);
+            (quote!(::std::option::Option<#inner_type>), inner_structs)
+        }
+
+        Shape::Union(members) => generate_enum(members, nested_name, path, ctx),
+
+        Shape::Tagged { tag, variants } => {
+            generate_tagged_enum(tag, variants, nested_name, path, ctx)
+        }
+
+        // Values of maps are named like the elements of arrays, `scores: HashMap<String, Score>`
+        Shape::Map(value) => {
+            let (value_type, value_structs) = match value.as_ref() {
+                Shape::Recursive(reference) => {
+                    recursive_type(reference, &map_value_path(path), ctx)
+                }
+                value => shape_type(value, nested_name, &map_value_path(path), ctx),
+            };
+            (
+                quote!(::std::collections::HashMap<::std::string::String, #value_type>),
+                value_structs,
+            )
+        }
+
+        Shape::Enum(values) => generate_string_enum(values, nested_name, path, ctx),
+
+        Shape::Null => (
+            quote!(::std::option::Option<::serde_json::Value>),
+            Vec::new(),
+        ),
+
+        Shape::Explicit(ty) => (ty.into_token_stream(), Vec::new()),
+
+        // A struct can't hold itself, so it's boxed unless a `Vec` or a map holds it
+        Shape::Recursive(reference) => {
+            let (struct_type, _) = recursive_type(reference, path, ctx);
+            (quote!(::std::boxed::Box<#struct_type>), Vec::new())
+        }
+
+        // A schema definition is generated once, named after it, and reused by every `$ref`
+        Shape::Named {
+            reference,
+            name,
+            shape,
+        } => {
+            if let Some(name) = ctx.definitions.get(reference).cloned() {
+                ctx.types.insert(path.to_string(), name.clone());
+                return (name.into_token_stream(), Vec::new());
+            }
+
+            let name = format_ident!("{}", name);
+
+            match shape.as_ref() {
+                // The name is known before the fields, so the ones that refer to the struct can use it
+                Shape::Object(fields) => {
+                    let name = ctx.names.claim(&name, &ctx.parent);
+                    ctx.definitions.insert(reference.clone(), name.clone());
+                    ctx.types.insert(path.to_string(), name.clone());
+
+                    let (nested_struct, mut nested_structs) =
+                        generate_struct(fields, &name, path, ctx);
+
+                    nested_structs.push(nested_struct);
+
+                    (name.into_token_stream(), nested_structs)
+                }
+                shape => {
+                    let generated = shape_type(shape, &name, path, ctx);
+
+                    if let Some(name) = ctx.types.get(path).cloned() {
+                        ctx.definitions.insert(reference.clone(), name);
+                    }
+
+                    generated
+                }
+            }
+        }
+
+        // Empty arrays, and conflicting types with `@lenient`
+        Shape::Unknown | Shape::Any => (quote!(::serde_json::Value), Vec::new()),
+    }
+}
+
+/// The struct a [`Shape::Recursive`] refers to, one of the structs being generated
+fn recursive_type(
+    reference: &str,
+    path: &str,
+    ctx: &mut Context,
 ) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
-    // Handle empty array
-    if arr.is_empty() {
-        return (quote!(::serde_json::Value), Vec::new());
+    match ctx.definitions.get(reference).cloned() {
+        Some(name) => {
+            ctx.types.insert(path.to_string(), name.clone());
+            (name.into_token_stream(), Vec::new())
+        }
+        None => (quote!(::serde_json::Value), Vec::new()),
     }
+}
+
+/// The path of the values of a field, the main struct is at `""`.
+///
+/// Keys are quoted so paths never clash, `."employees"[]."details"`.
+pub fn field_path(path: &str, key: &str) -> String {
+    format!("{}.{:?}", path, key)
+}
 
-    // Infer type based on first element
-    match &arr[0] {
-        JsonValue::Str(_) => (quote!(String), Vec::new()),
-        JsonValue::Number(_) => (quote!(f64), Vec::new()),
-        JsonValue::Boolean(_) => (quote!(bool), Vec::new()),
-        _ => (quote!(::serde_json::Value), Vec::new()),
+/// The path of the elements of an array
+pub fn element_path(path: &str) -> String {
+    format!("{}[]", path)
+}
+
+/// The path of the values of a map
+pub fn map_value_path(path: &str) -> String {
+    format!("{}{{}}", path)
+}
+
+/// The path of the values of an enum variant, by its name (`Obj`) or tag value (`"click"`)
+pub fn variant_path(path: &str, variant: &str) -> String {
+    format!("{}<{:?}>", path, variant)
+}
+
+/// Builds the `#[serde(...)]` attribute that gives a field its exact name in the JSON.
+///
+/// Each direction uses its rename style flag when there's one, and the original key otherwise,
+/// with `@alias` the original key is also accepted when deserializing.
+///
+/// # Parameters
+/// - `field_name`: The name of the field
+/// - `key`: The original key
+/// - `struct_styles`: The serialize and deserialize styles in the struct's `rename_all`
+/// - `flags`: The macro flags, with the rename styles
+///
+/// # Returns
+/// The attribute, or `None` when the struct's `rename_all` already gives the right names
+fn rename_attr(
+    field_name: &Ident,
+    key: &str,
+    struct_styles: (&Option<RenameStyle>, &Option<RenameStyle>),
+    flags: &JsonMacroFlags,
+) -> Option<proc_macro2::TokenStream> {
+    let field_name = field_name.unraw().to_string();
+
+    // The names serde gives the field from the struct's `rename_all`
+    let derived = |style: &Option<RenameStyle>| match style {
+        Some(style) => style.apply(&field_name),
+        None => field_name.clone(),
+    };
+
+    // The names the field should have
+    let wanted = |style: &Option<RenameStyle>| match style {
+        Some(style) => style.apply(&field_name),
+        None => key.to_string(),
+    };
+
+    let serialize = serialized_name(&field_name, key, flags);
+    let mut deserialize = wanted(&flags.rename_deserialize);
+    let mut alias = None;
+
+    // The key stays the main name, so errors about the field use it
+    if flags.use_serde_alias && deserialize != key {
+        alias = Some(std::mem::replace(&mut deserialize, key.to_string()));
+    }
+
+    let rename = if serialize == derived(struct_styles.0) && deserialize == derived(struct_styles.1)
+    {
+        None
+    } else if serialize == deserialize {
+        Some(quote!(rename = #serialize))
+    } else {
+        Some(quote!(rename(serialize = #serialize, deserialize = #deserialize)))
+    };
+
+    let alias = alias.map(|alias| quote!(alias = #alias));
+
+    let parts = rename.into_iter().chain(alias).collect::<Vec<_>>();
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(quote!(#[serde(#(#parts),*)]))
     }
 }
 
-/// Sanitizes a string to create a valid Rust identifier.
+/// The name a field is serialized with, its key unless there's a serialize rename style.
 ///
 /// # Parameters
-/// - `name`: The input string to sanitize
+/// - `field_name`: The name of the field, without `r#`
+/// - `key`: The original key
+/// - `flags`: The macro flags, with the rename styles
+pub fn serialized_name(field_name: &str, key: &str, flags: &JsonMacroFlags) -> String {
+    match &flags.rename_serialize {
+        Some(style) => style.apply(field_name),
+        None => key.to_string(),
+    }
+}
+
+/// Determines the derives every generated type gets.
+fn derives(flags: &JsonMacroFlags) -> Vec<proc_macro2::TokenStream> {
+    // Determine base derives
+    //
+    // usually clone is needed for json, so by default, it get's derived
+    let mut derives = vec![quote!(::std::clone::Clone)];
+
+    // Conditionally add derives based on flags
+    //
+    // not really need to be a seprate flag, but it's nice to have a quick way to do so
+    if flags.debug {
+        derives.push(quote!(::std::fmt::Debug));
+    }
+
+    // Collected from the `@derive(...)`
+    derives.extend(flags.custom_derives.iter().map(|d| quote!(#d)));
+
+    derives
+}
+
+/// Picks the Rust type for integers.
+///
+/// The `@int(...)` type (or `i64`) is used when it fits every value seen,
+/// widening to `i64` then `u64` when it doesn't.
+///
+/// # Parameters
+/// - `min`, `max`: The smallest and largest value the type has to hold
+/// - `flags`: The macro flags, used to pick the integer type
 ///
 /// # Returns
-/// A sanitized, lowercase string suitable for use as a Rust identifier
-fn sanitize_identifier(name: &str) -> String {
-    name.chars()
-        .map(|c| if c.is_alphanumeric() { c } else { '_' })
-        .collect::<String>()
-        .to_lowercase()
+/// The integer type as a token stream, or `None` when no type holds every value,
+/// integer shapes are checked for it when they're built (see [`shape::int`])
+pub fn integer_type(
+    min: i128,
+    max: i128,
+    flags: &JsonMacroFlags,
+) -> Option<proc_macro2::TokenStream> {
+    let preferred = flags
+        .int_type
+        .clone()
+        .unwrap_or_else(|| format_ident!("i64"));
+
+    [preferred, format_ident!("i64"), format_ident!("u64")]
+        .into_iter()
+        .find(|ty| {
+            integer_range(&ty.to_string())
+                .is_some_and(|(ty_min, ty_max)| ty_min <= min && max <= ty_max)
+        })
+        .map(ToTokens::into_token_stream)
 }
@@ -0,0 +1,200 @@
+use crate::parser::{is_json_number, JsonNumber, JsonValue};
+
+/// Reads JSON text into the values inline samples give.
+///
+/// Numbers keep the text they were written as, so `1e2` stays a float, `18446744073709551616`
+/// stays an integer and both are stored back exactly as they were read.
+/// Keys stay in the order they were written.
+///
+/// # Errors
+/// If the text isn't valid JSON, with the line and column where reading stopped
+pub fn read(text: &str) -> Result<JsonValue, String> {
+    let mut reader = Reader { text, pos: 0 };
+
+    let value = reader.value()?;
+    reader.skip_whitespace();
+
+    if reader.pos < text.len() {
+        return Err(reader.error("trailing characters"));
+    }
+
+    Ok(value)
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    /// Byte offset of the next character
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(JsonValue::Str),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) if self.eat("true") => Ok(JsonValue::Boolean(true)),
+            Some(_) if self.eat("false") => Ok(JsonValue::Boolean(false)),
+            Some(_) if self.eat("null") => Ok(JsonValue::Null),
+            _ => Err(self.error("expected value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, String> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+
+        self.skip_whitespace();
+        if self.eat("}") {
+            return Ok(JsonValue::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            entries.push((key, self.value()?));
+
+            self.skip_whitespace();
+            if self.eat("}") {
+                return Ok(JsonValue::Object(entries));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, String> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.eat("]") {
+            return Ok(JsonValue::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            self.skip_whitespace();
+            if self.eat("]") {
+                return Ok(JsonValue::Array(values));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    /// Reads a string, serde_json decodes the escapes
+    fn string(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        let mut chars = self.text[start + 1..].char_indices();
+
+        let len = loop {
+            match chars.next() {
+                Some((i, '"')) => break i + 2,
+                Some((_, '\\')) => {
+                    chars.next();
+                }
+                Some(_) => {}
+                None => return Err(self.error("unterminated string")),
+            }
+        };
+
+        let string = serde_json::from_str(&self.text[start..start + len])
+            .map_err(|e| self.error(&format!("invalid string, {}", e)))?;
+        self.pos += len;
+
+        Ok(string)
+    }
+
+    /// Reads a number, keeping its text
+    fn number(&mut self) -> Result<JsonValue, String> {
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(rest.len());
+        let text = &rest[..len];
+
+        let number = JsonNumber::from_text(text)
+            .filter(|_| is_json_number(text))
+            .ok_or_else(|| self.error(&format!("invalid number `{}`", text)))?;
+        self.pos += len;
+
+        Ok(JsonValue::Number(number))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    /// Moves past `token` if the text continues with it
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.text[self.pos..].starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c.encode_utf8(&mut [0; 4])) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)))
+        }
+    }
+
+    /// Describes a problem at the current position, `expected value at line 3 column 12`
+    fn error(&self, message: &str) -> String {
+        let before = &self.text[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+
+        format!("{} at line {} column {}", message, line, column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_number_text_and_key_order() {
+        let value = read(r#"{ "z": 1e2, "a": [18446744073709551616, -0, "é\n"] }"#).unwrap();
+
+        let mut json = String::new();
+        value.write_json(&mut json).unwrap();
+
+        assert_eq!(json, r#"{"z":1e2,"a":[18446744073709551616,-0,"é\n"]}"#);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        let cases = [
+            (
+                "{\n  \"a\": 01\n}",
+                "invalid number `01` at line 2 column 8",
+            ),
+            ("[1, 2", "expected `,` at line 1 column 6"),
+            ("{\"a\": 1,}", "expected a string key at line 1 column 9"),
+            ("{} {}", "trailing characters at line 1 column 4"),
+            ("\"abc", "unterminated string at line 1 column 1"),
+        ];
+
+        for (text, message) in cases {
+            assert_eq!(read(text).unwrap_err(), message, "reading {:?}", text);
+        }
+    }
+}
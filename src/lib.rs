@@ -10,45 +10,52 @@
 //! - Serde integration
 //! - Compile-time type checking
 //! - Multiple configuration flags
+//! - Structs from JSON Schema files with [`json2struct_schema!`]
 //!
 //! ## Basic Usage
 //!
 //! ```rust
+//! # use json_to_struct::json2struct;
 //! // Simple struct generation
 //! json2struct!(User {
-//!     "first_name" => "John",
-//!     "last_name" => "Doe",
-//!     "age" => 30
+//!     "first_name": "John",
+//!     "last_name": "Doe",
+//!     "age": 30
 //! });
 //! ```
 //!
 //! ### Output
-//! ```rust
+//! ```rust,ignore
 //! #[derive(Clone, Deserialize, Serialize)]
 //! struct User {
-//!   #[serde(alias = "first_name")]
 //!   first_name: String,
 //!
-//!   #[serde(alias = "last_name")]
 //!   last_name: String,
 //!
-//!   #[serde(alias = "age")]
-//!   age: f64
+//!   age: i64
 //! }
 //! ```
 //!
+//! Real JSON can be pasted unchanged as a string literal:
+//!
+//! ```rust
+//! # use json_to_struct::json2struct;
+//! json2struct!(User, r#"{"first_name": "John", "age": 30}"#);
+//! ```
+//!
 //! ## Example with Flags
 //!
 //! ```rust
+//! # use json_to_struct::json2struct;
 //! // Complex struct with multiple configurations
 //! json2struct!(Company @debug @camel @derive(PartialEq) @store_json {
-//!     "company_name" => "Acme Corp",
-//!     "employees" => [
+//!     "company_name": "Acme Corp",
+//!     "employees": [
 //!         {
-//!             "id" => 1,
-//!             "details" => {
-//!                 "email" => "john@example.com",
-//!                 "department" => "Engineering"
+//!             "id": 1,
+//!             "details": {
+//!                 "email": "john@example.com",
+//!                 "department": "Engineering"
 //!             }
 //!         }
 //!     ]
@@ -57,49 +64,53 @@
 //!
 //! ### Output
 //!
-//! ```rust
-//!
+//! ```rust,ignore
+//! const COMPANY_JSON_TEXT: &str =
+//!   "{\"company_name\":\"Acme Corp\",\"employees\":[{\"id\":1,\"details\":{\"email\":\"john@example.com\",\"department\":\"Engineering\"}}]}";
 //!
-//! static COMPANY_JSON_VALUE: LazyLock<Value> = LazyLock::new(||
-//!
-//! {
-//!   ::serde_json::from_str(
-//!           "{\"company_name\":\"Acme Corp\",\"employees\":[{\"details\":{\"department\":\"Engineering\",\"email\":\"john@example.
-//! com\"},\"id\":1.0}]}",
-//!        )
+//! static COMPANY_JSON_VALUE: LazyLock<Value> = LazyLock::new(|| {
+//!   ::serde_json::from_str(COMPANY_JSON_TEXT)
 //!        .expect("Couldn't convert the text into valid json")
 //!   });
 //!
 //! #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 //! #[serde(rename_all = "camelCase")]
 //! struct Company {
-//!   #[serde(alias = "company_name")]
 //!   company_name: String,
 //!
-//!   #[serde(alias = "last_name")]
-//!   employees: CompanyEmplyees
+//!   employees: Vec<Employee>
 //! }
 //!
 //! #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 //! #[serde(rename_all = "camelCase")]
-//! struct CompanyEmplyees {
-//!   #[serde(alias = "id")]
-//!   id: f64,
+//! struct Employee {
+//!   id: i64,
 //!
-//!   #[serde(alias = "details")]
-//!   details:  
+//!   details: Details
 //! }
 //!
 //! #[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
 //! #[serde(rename_all = "camelCase")]
-//! struct CompanyEmplyeesDetails {
-//!   #[serde(alias = "email")]
+//! struct Details {
 //!   email: String,
 //!
-//!   #[serde(alias = "department")]
 //!   department: String
 //! }
+//! ```
 //!
+//! ## Typed Samples
+//!
+//! `@sample` builds the sample as a struct literal, with no JSON parsed at runtime:
+//!
+//! ```rust
+//! # use json_to_struct::json2struct;
+//! json2struct!(Config @sample {
+//!     "name": "app",
+//!     "port": 8080 as u16
+//! });
+//!
+//! let config = Config::sample();
+//! assert_eq!(config.port, 8080);
 //! ```
 //!
 //! ## Supported Flags
@@ -110,50 +121,99 @@
 //! | `@snake`        | Renames fields to snake_case                  | `@snake`                      |
 //! | `@camel`        | Renames fields to camelCase                   | `@camel`                      |
 //! | `@pascal`       | Renames fields to pascal                      | `@pascal`                     |
+//! | `@rename_all(..)` | Renames fields with any serde style         | `@rename_all("kebab-case")`   |
+//! | `@rename_serialize(..)` | Renames fields when serializing       | `@rename_serialize("snake_case")` |
+//! | `@rename_deserialize(..)` | Renames fields when deserializing   | `@rename_deserialize("camelCase")` |
+//! | `@alias`        | Also deserializes renamed fields from the original keys | `@camel @alias`     |
 //! | `@derive(Type)` | Adds custom derives                           | `@derive(PartialEq, Clone)`   |
-//! | `@store_json`   | Generates a static JSON Value constant        | `@store_json`                 |
+//! | `@store_json`   | Generates the sample as JSON text and a static JSON Value | `@store_json`     |
+//! | `@sample`       | Generates `sample()`, returning the sample as a typed value | `@sample`       |
+//! | `@default_from_sample` | Generates `Default` impls returning the sample values | `@default_from_sample` |
+//! | `@builder`      | Generates a builder for every struct          | `@builder`                    |
+//! | `@fields`       | Generates a `{Name}Field` enum with `get`/`set` for every struct | `@fields`  |
+//! | `@json_schema`  | Generates the JSON Schema of the main struct  | `@json_schema`                |
+//! | `@file(path)`   | Reads samples from JSON files                 | `@file("fixtures/user.json")` |
+//! | `@samples([..])`| Unifies several samples, missing fields become `Option` | `@samples([{..}, {..}])` |
+//! | `@int(type)`    | Sets the integer type (default `i64`)         | `@int(i32)`                   |
+//! | `@lenient`      | Uses `Value` for conflicting types            | `@lenient`                    |
+//! | `@untagged`     | Uses untagged enums for conflicting types     | `@untagged`                   |
+//! | `@tagged(field)`| Groups array objects by a tag field into an enum | `@tagged("type")`          |
+//! | `@pub`          | Makes every generated type public             | `@pub`, `@pub(crate)`         |
+//! | `@vis(..)`      | Sets the visibility of every generated type   | `@vis(pub(super))`            |
+//! | `@field_vis(..)`| Sets the visibility of every generated field  | `@field_vis(pub)`             |
+//! | `@dedupe(..)`   | How identical nested structs are shared (`common`, `first`, `off`) | `@dedupe(first)` |
+//! | `@share(Name)`  | Names a shared nested struct                  | `@share(Address = "billing")` |
+//! | `@prefix_nested`| Prefixes nested type names with their parent's name | `@prefix_nested`        |
 //!
 
 extern crate proc_macro;
 
+mod builder;
+mod dedupe;
+mod fields;
+mod from_schema;
 mod generator;
+mod json_text;
+mod naming;
 mod parser;
+mod sample;
+mod schema;
+mod shape;
 
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::parse_macro_input;
 
+// The examples in the README are compiled as doctests, so they keep up with the macros
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+struct ReadmeDoctests;
+
 /// json2struct: Generates Rust structs from JSON-like structures
 ///
 /// # Macro Syntax
 ///
-/// ```rust
+/// ```rust,ignore
 /// json2struct!(StructName [flags] {
-///     "key" => value,
+///     "key": value,
 ///     ...
 /// })
+///
+/// json2struct!(StructName [flags], r#"{ "key": value, ... }"#)
 /// ```
 ///
+/// Keys can be separated from values with either `:` or `=>`,
+/// and the older `{{ ... }}` form is still accepted.
+///
 /// # Supported Value Types
 /// - Strings: `"value"`
-/// - Numbers: `42`, `3.14`
+/// - Numbers: `42`, `-3.14`, `1e-9` (integers become `i64`, or the `@int(...)` type, widening to `u64` when
+///   the values need it, and floats `f64`)
 /// - Booleans: `true`, `false`
-/// - Null: `null`
+/// - Null: `null`, or `null as Type` to give the type of the `Option<Type>` field
+///
+/// Any value can be followed by `as Type` (or `: Type`) to use that type verbatim instead of inferring one,
+/// like `8080 as u16`, `"x" as uuid::Uuid` or `[] as Vec<String>`.
 /// - Objects: `{ ... }`
-/// - Arrays: `[ ... ]`
+/// - Arrays: `[ ... ]` (arrays of objects become `Vec` of a generated struct)
+///
+/// Every element of an array is merged into one element type: fields missing from some objects
+/// and values that are sometimes `null` become `Option<T>`, and integers next to floats widen to `f64`.
 ///
 /// # Examples
 ///
 /// Basic Struct:
 /// ```rust
+/// # use json_to_struct::json2struct;
 /// json2struct!(User {
-///     "name" => "John",
-///     "age" => 30
+///     "name": "John",
+///     "age": 30
 /// });
 /// ```
 ///
 /// Nested Struct:
 /// ```rust
+/// # use json_to_struct::json2struct;
 /// json2struct!(Company @debug {
 ///     "name" => "Acme",
 ///     "address" => {
@@ -163,6 +223,20 @@ use syn::parse_macro_input;
 /// });
 /// ```
 ///
+/// Sample loaded from a file (relative to `CARGO_MANIFEST_DIR`):
+/// ```rust,ignore
+/// json2struct!(Order @debug @file("fixtures/order.json"));
+/// ```
+///
+/// Several samples, fields missing from some of them become `Option<T>` with `#[serde(default)]`:
+/// ```rust
+/// # use json_to_struct::json2struct;
+/// json2struct!(Ticket @samples([
+///     { "id": 1, "assignee": "ana" },
+///     { "id": 2, "closed_at": "2024-01-01" }
+/// ]));
+/// ```
+///
 /// # Performance
 /// - Zero-cost abstraction
 /// - Compile-time struct generation
@@ -173,6 +247,8 @@ use syn::parse_macro_input;
 /// - Invalid JSON structure
 /// - Unsupported types
 /// - Conflicting flags
+/// - Array elements have conflicting types (unless `@lenient` is set)
+/// - The `@file(...)` sample can't be read or isn't a JSON object
 #[proc_macro]
 pub fn json2struct(input: TokenStream) -> TokenStream {
     // Parse the input into our custom macro input structure
@@ -183,40 +259,58 @@ pub fn json2struct(input: TokenStream) -> TokenStream {
 
     // Optionally generate a static JSON value constant
     if json_struct.flags.store_json_value {
-        // Convert entries to serde_json::Value
-        let serde_value = serde_json::Value::Object(
-            json_struct
-                .content
-                .entries
-                .clone()
-                .into_iter()
-                .map(|(k, v)| (k, v.to_serde_value()))
-                .collect(),
-        );
-
-        // Convert to string for lazy initialization
-        let serde_value_str = serde_json::to_string(&serde_value).unwrap_or_default();
+        // Write the sample as JSON text, with the keys in order and the numbers as they were written
+        let json_text = match json_struct.content.to_json_text() {
+            Ok(json_text) => json_text,
+            Err(message) => {
+                return syn::Error::new(json_struct.struct_name.span(), message)
+                    .to_compile_error()
+                    .into()
+            }
+        };
 
-        // Generate a constant name based on struct name
+        // Generate constant names based on struct name
         let const_json_ident = format_ident!(
             "{}_{}",
             json_struct.struct_name.to_string().to_uppercase(),
             "JSON_VALUE"
         );
+        let const_text_ident = format_ident!(
+            "{}_{}",
+            json_struct.struct_name.to_string().to_uppercase(),
+            "JSON_TEXT"
+        );
+
+        let vis = &json_struct.flags.vis;
 
-        // Generate lazy-loaded static JSON value
+        // The text is kept too, `Value` only keeps the key order and number form
+        // when serde_json has the `preserve_order` and `arbitrary_precision` features
         output.extend(quote! {
-            static #const_json_ident: ::std::sync::LazyLock<::serde_json::Value> =
+            #vis const #const_text_ident: &str = #json_text;
+
+            #vis static #const_json_ident: ::std::sync::LazyLock<::serde_json::Value> =
                 ::std::sync::LazyLock::new(||
-                    ::serde_json::from_str(#serde_value_str)
+                    ::serde_json::from_str(#const_text_ident)
                         .expect("Couldn't convert the text into valid json")
                 );
         });
     }
 
+    // Make the compiler rebuild when the sample file changes, the same way `include_str!` does
+    for source_file in &json_struct.source_files {
+        let source_file = source_file.to_string_lossy();
+
+        output.extend(quote! {
+            const _: &[u8] = ::std::include_bytes!(#source_file);
+        });
+    }
+
     // Generate the main struct and any nested structs
     let (main_struct, all_structs) =
-        generator::generate_structs(&json_struct, &json_struct.struct_name);
+        match generator::generate_structs(&json_struct, &json_struct.struct_name) {
+            Ok(structs) => structs,
+            Err(err) => return err.to_compile_error().into(),
+        };
 
     // Combine all generated code
     output.extend(quote! {
@@ -227,3 +321,83 @@ pub fn json2struct(input: TokenStream) -> TokenStream {
     // Convert to TokenStream for the compiler
     output.into()
 }
+
+/// json2struct_schema: Generates Rust structs from a JSON Schema file
+///
+/// # Macro Syntax
+///
+/// ```rust,ignore
+/// json2struct_schema!("schemas/order.json")
+///
+/// json2struct_schema!(StructName [flags], "schemas/order.json")
+/// ```
+///
+/// The path is relative to `CARGO_MANIFEST_DIR`, and without a name the struct is named after the schema's `title`.
+/// The types are the same ones `json2struct!` generates, with the same flags, except the ones that need a sample
+/// (`@samples`, `@file`, `@sample`, `@default_from_sample` and `@store_json`).
+///
+/// # Supported Keywords
+/// - `type`, `properties` and `required`: fields that aren't required become `Option<T>`
+/// - `items`: the elements of arrays
+/// - `additionalProperties`: objects without `properties` become `HashMap<String, T>`
+/// - `enum`: strings become an enum with one variant per value
+/// - `format`: `int32`, `uint64`, ... become sized integers and `float` becomes `f32`
+/// - `oneOf`/`anyOf`: members are merged like samples, `null` makes the value optional,
+///   and objects told apart by a `const` property become a tagged enum
+/// - `allOf`: the objects are joined into one
+/// - `$ref`/`$defs`: references inside the schema are followed, the objects and enums they point at
+///   are generated once, named after their definition (`#/$defs/Address` → `Address`).
+///   An object that refers to itself holds its own struct (`Vec<Node>`, or `Option<Box<Node>>` without
+///   a `Vec` or map around it), other types that refer to themselves are a `serde_json::Value` where they do
+///
+/// # Examples
+///
+/// ```rust
+/// # use json_to_struct::json2struct_schema;
+/// json2struct_schema!(Order @debug, "examples/fixtures/order.schema.json");
+/// ```
+///
+/// # Errors
+/// Compilation will fail if:
+/// - The schema file can't be read or isn't JSON
+/// - The schema doesn't describe an object, or uses a `$ref` outside of the document
+/// - The members of a `oneOf`/`anyOf` have conflicting types (unless `@untagged` or `@lenient` is set)
+#[proc_macro]
+pub fn json2struct_schema(input: TokenStream) -> TokenStream {
+    // Parse the input into our custom macro input structure
+    let schema_input = parse_macro_input!(input as parser::SchemaMacroInput);
+
+    // Read the fields of the main struct from the schema, errors point at the path
+    let fields = match from_schema::schema_fields(&schema_input.schema, &schema_input.flags) {
+        Ok(fields) => fields,
+        Err(message) => {
+            return syn::Error::new(schema_input.path.span(), message)
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    // Generate the main struct and any nested types, there's no sample to build
+    let (main_struct, all_structs) = match generator::generate_types(
+        &fields,
+        &schema_input.struct_name,
+        &[],
+        &schema_input.flags,
+    ) {
+        Ok(structs) => structs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    // Make the compiler rebuild when the schema changes, the same way `include_str!` does
+    let source_file = schema_input.source_file.to_string_lossy();
+
+    let output = quote! {
+        const _: &[u8] = ::std::include_bytes!(#source_file);
+
+        #main_struct
+        #(#all_structs)*
+    };
+
+    // Convert to TokenStream for the compiler
+    output.into()
+}
@@ -0,0 +1,579 @@
+use std::collections::HashSet;
+
+use inflections::Inflect;
+use quote::format_ident;
+use syn::{ext::IdentExt, Ident};
+
+use crate::parser::{JsonMacroFlags, RenameStyle};
+
+/// Words that don't follow the usual plural rules
+const IRREGULAR_PLURALS: &[(&str, &str)] = &[
+    ("people", "person"),
+    ("children", "child"),
+    ("men", "man"),
+    ("women", "woman"),
+    ("mice", "mouse"),
+    ("geese", "goose"),
+    ("teeth", "tooth"),
+    ("feet", "foot"),
+    ("indices", "index"),
+    ("matrices", "matrix"),
+    ("vertices", "vertex"),
+    ("criteria", "criterion"),
+    ("phenomena", "phenomenon"),
+    ("movies", "movie"),
+    ("cookies", "cookie"),
+    ("caches", "cache"),
+];
+
+/// Words whose plural turns `f` or `fe` into `ves`, without the `ves`
+const F_PLURALS: &[(&str, &str)] = &[
+    ("lea", "f"),
+    ("hal", "f"),
+    ("shel", "f"),
+    ("wol", "f"),
+    ("cal", "f"),
+    ("thie", "f"),
+    ("loa", "f"),
+    ("sel", "f"),
+    ("kni", "fe"),
+    ("wi", "fe"),
+    ("li", "fe"),
+];
+
+/// Words that are the same in singular and plural, or already look plural when singular
+const UNCOUNTABLE: &[&str] = &[
+    "data",
+    "info",
+    "news",
+    "series",
+    "species",
+    "metadata",
+    "media",
+    "equipment",
+    "sheep",
+    "fish",
+    "status",
+    "analysis",
+    "basis",
+    "axis",
+    "bus",
+    "alias",
+    "canvas",
+    "address",
+    "access",
+    "process",
+    "class",
+    "glass",
+    "business",
+];
+
+/// Keywords that can be used as identifiers with `r#`
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "try", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// Keywords that can't be raw identifiers, they get a trailing `_` instead
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate"];
+
+/// Names from the standard prelude, a generated type with one of them would shadow it in the caller's module
+const PRELUDE_NAMES: &[&str] = &[
+    "Option",
+    "Some",
+    "None",
+    "Result",
+    "Ok",
+    "Err",
+    "String",
+    "Vec",
+    "Box",
+    "ToString",
+    "ToOwned",
+    "Clone",
+    "Copy",
+    "Send",
+    "Sync",
+    "Sized",
+    "Unpin",
+    "Drop",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "Iterator",
+    "IntoIterator",
+    "DoubleEndedIterator",
+    "ExactSizeIterator",
+    "Extend",
+    "FromIterator",
+    "Default",
+    "Debug",
+    "Hash",
+    "Eq",
+    "PartialEq",
+    "Ord",
+    "PartialOrd",
+    "AsRef",
+    "AsMut",
+    "Into",
+    "From",
+    "TryFrom",
+    "TryInto",
+];
+
+/// Turns a plural English word into its singular form using common rules.
+///
+/// `employees` → `employee`, `categories` → `category`, `addresses` → `address`
+///
+/// # Parameters
+/// - `word`: A lowercase word
+///
+/// # Returns
+/// The singular form, or the word itself if it doesn't look plural
+pub fn singularize(word: &str) -> String {
+    if UNCOUNTABLE.contains(&word) {
+        return word.to_string();
+    }
+
+    if let Some((_, singular)) = IRREGULAR_PLURALS.iter().find(|(plural, _)| *plural == word) {
+        return singular.to_string();
+    }
+
+    // Words ending in `ss`, `us` or `is` are usually already singular, `class`, `status`, `basis`
+    if word.ends_with("ss") || word.ends_with("us") || word.ends_with("is") {
+        return word.to_string();
+    }
+
+    if let Some(stem) = word.strip_suffix("ies") {
+        if stem.len() > 1 {
+            return format!("{}y", stem);
+        }
+    }
+
+    // `knives` → `knife`, `leaves` → `leaf`, but `moves` → `move`
+    if let Some(stem) = word.strip_suffix("ves") {
+        if let Some((_, ending)) = F_PLURALS.iter().find(|(f_stem, _)| stem.ends_with(f_stem)) {
+            return format!("{}{}", stem, ending);
+        }
+    }
+
+    // `addresses`, `boxes`, `matches`, `wishes`, `buzzes`
+    for suffix in ["sses", "xes", "ches", "shes", "zzes"] {
+        if word.ends_with(suffix) {
+            return word[..word.len() - 2].to_string();
+        }
+    }
+
+    // `statuses` → `status`, but `houses` → `house`
+    if let Some(stem) = word.strip_suffix("uses") {
+        if stem.ends_with(|c: char| !"aeiou".contains(c)) {
+            return format!("{}us", stem);
+        }
+    }
+
+    match word.strip_suffix('s') {
+        Some(stem) if !stem.is_empty() => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+/// Splits a key into lowercase words, at anything that isn't an ASCII letter or digit and where the case changes.
+///
+/// `userId` → `user`, `id`, and acronyms stay one word, `HTTPStatus` → `http`, `status`.
+/// Digits stay with the word before them, `address2Line` → `address2`, `line`.
+///
+/// # Parameters
+/// - `name`: The key
+pub fn split_words(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+
+        // A capital after a lowercase letter or a digit starts a word, `userId`, `v2Beta`,
+        // and so does the last capital of an acronym followed by a lowercase letter, `HTTPStatus`
+        let starts_word = c.is_ascii_uppercase()
+            && prev.is_some_and(|prev| {
+                prev.is_ascii_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_ascii_uppercase() && next.is_some_and(char::is_ascii_lowercase))
+            });
+
+        if starts_word && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+
+        word.push(c.to_ascii_lowercase());
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Converts a string to a PascalCase type name, like a key or a tag value.
+///
+/// # Parameters
+/// - `name`: The input string
+/// - `singular`: Whether the last word should be singularized, for the elements of an array
+///
+/// # Returns
+/// A PascalCase string suitable for use as a type or variant name
+pub fn type_name(name: &str, singular: bool) -> String {
+    let mut words = split_words(name);
+
+    if singular {
+        if let Some(last) = words.last_mut() {
+            *last = singularize(last);
+        }
+    }
+
+    let name = words.join("_").to_pascal_case();
+
+    // Type names can't be empty, start with a digit or be `Self`
+    if name.chars().next().is_none_or(|c| c.is_ascii_digit()) {
+        format!("V{}", name)
+    } else if NON_RAW_KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+/// Converts a key to a snake_case field identifier, unique among the fields of its struct.
+///
+/// `userId` → `user_id`, `type` → `r#type`, `2fa` → `field_2fa`, `self` → `self_`,
+/// and a second `a_b` after `a-b` → `a_b_2`.
+///
+/// # Parameters
+/// - `key`: The key of the field
+/// - `taken`: The field names already used in the struct, the new one is added to it
+///
+/// # Returns
+/// A valid Rust identifier
+pub fn field_ident(key: &str, taken: &mut HashSet<String>) -> Ident {
+    let name = split_words(key).join("_");
+
+    // Identifiers can't be empty or start with a digit
+    let name = if name.is_empty() {
+        "field".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("field_{}", name)
+    } else if NON_RAW_KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    };
+
+    // Keys that end up with the same name, like `a-b` and `a_b`, are numbered
+    let name = if taken.contains(&name) {
+        (2..)
+            .map(|n| format!("{}_{}", name, n))
+            .find(|name| !taken.contains(name))
+            .unwrap_or_default()
+    } else {
+        name
+    };
+
+    taken.insert(name.clone());
+
+    if KEYWORDS.contains(&name.as_str()) {
+        Ident::new_raw(&name, proc_macro2::Span::call_site())
+    } else {
+        format_ident!("{}", name)
+    }
+}
+
+/// Builds the name a nested type would like to have.
+///
+/// Nested types get short names (`details` → `Details`), unless `@prefix_nested` is set,
+/// in which case they're prefixed with the name of their parent (`CompanyDetails`).
+///
+/// # Parameters
+/// - `parent`: The name of the struct the field is in
+/// - `key`: The key of the field
+/// - `singular`: Whether the field is an array, so its elements get a singular name
+/// - `flags`: The macro flags
+pub fn nested_type_name(
+    parent: &Ident,
+    key: &str,
+    singular: bool,
+    flags: &JsonMacroFlags,
+) -> Ident {
+    let name = type_name(key, singular);
+
+    if flags.prefix_nested {
+        format_ident!("{}{}", parent, name)
+    } else {
+        format_ident!("{}", name)
+    }
+}
+
+/// Finds the naming convention every key of a struct follows.
+///
+/// A style is picked when serde's `rename_all` with it turns every field name back into its key,
+/// `{"userId": 1, "firstName": ""}` gives `camelCase`.
+///
+/// # Parameters
+/// - `fields`: The field names of the struct, with their keys
+///
+/// # Returns
+/// The style, or `None` when the keys are already snake_case or mix conventions
+pub fn detect_key_style(fields: &[(Ident, String)]) -> Option<RenameStyle> {
+    let fields = fields
+        .iter()
+        .map(|(field_name, key)| (field_name.unraw().to_string(), key))
+        .collect::<Vec<_>>();
+
+    // Nothing to do when every field already has the name of its key
+    if fields.iter().all(|(field_name, key)| field_name == *key) {
+        return None;
+    }
+
+    [
+        RenameStyle::Camel,
+        RenameStyle::Pascal,
+        RenameStyle::Kebab,
+        RenameStyle::ScreamingSnake,
+        RenameStyle::ScreamingKebab,
+    ]
+    .into_iter()
+    .find(|style| {
+        fields
+            .iter()
+            .all(|(field_name, key)| style.apply(field_name) == **key)
+    })
+}
+
+/// Every type name taken so far in one macro invocation, so generated types never collide
+#[derive(Debug, Default)]
+pub struct Names {
+    taken: HashSet<String>,
+}
+
+impl Names {
+    /// Starts with the name of the main struct taken, and the prelude's names,
+    /// so `"result": {...}` in `Rpc` gives `RpcResult` instead of shadowing `Result`
+    pub fn new(root: &Ident) -> Self {
+        let mut names = Names::default();
+        names.taken.insert(root.to_string());
+        names
+            .taken
+            .extend(PRELUDE_NAMES.iter().map(|name| name.to_string()));
+        names
+    }
+
+    /// Takes a name exactly as it is, like one given with `@share(...)`
+    ///
+    /// # Returns
+    /// `false` if the name was already taken
+    pub fn reserve(&mut self, name: &Ident) -> bool {
+        self.taken.insert(name.to_string())
+    }
+
+    /// Takes a name, disambiguating it if it's already taken.
+    ///
+    /// The parent's name is tried as a prefix first (`Data` → `ItemData`),
+    /// then a number is appended (`Data2`, `Data3`, ...), so the result only depends on the order names are claimed in.
+    ///
+    /// # Parameters
+    /// - `candidate`: The wanted name
+    /// - `parent`: The name of the type the new type is nested in
+    pub fn claim(&mut self, candidate: &Ident, parent: &Ident) -> Ident {
+        let candidate = candidate.to_string();
+        let parent = parent.to_string();
+
+        let mut options = vec![candidate.clone()];
+
+        if !candidate.starts_with(&parent) {
+            options.push(format!("{}{}", parent, candidate));
+        }
+
+        let name = options
+            .into_iter()
+            .find(|name| !self.taken.contains(name))
+            .unwrap_or_else(|| {
+                (2..)
+                    .map(|n| format!("{}{}", candidate, n))
+                    .find(|name| !self.taken.contains(name))
+                    .unwrap_or_default()
+            });
+
+        self.taken.insert(name.clone());
+
+        format_ident!("{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_words() {
+        let cases: &[(&str, &[&str])] = &[
+            ("userId", &["user", "id"]),
+            ("first_name", &["first", "name"]),
+            ("first-name", &["first", "name"]),
+            ("HTTPStatus", &["http", "status"]),
+            ("getHTTPResponseCode", &["get", "http", "response", "code"]),
+            ("URL", &["url"]),
+            ("address2Line", &["address2", "line"]),
+            ("2fa", &["2fa"]),
+            ("SCREAMING_SNAKE", &["screaming", "snake"]),
+            ("  spaced  out ", &["spaced", "out"]),
+            ("½", &[]),
+            ("café", &["caf"]),
+        ];
+
+        for (name, words) in cases {
+            assert_eq!(split_words(name), *words, "splitting {:?}", name);
+        }
+    }
+
+    #[test]
+    fn singularizes() {
+        let cases = [
+            ("employees", "employee"),
+            ("categories", "category"),
+            ("addresses", "address"),
+            ("boxes", "box"),
+            ("matches", "match"),
+            ("knives", "knife"),
+            ("leaves", "leaf"),
+            ("moves", "move"),
+            ("statuses", "status"),
+            ("houses", "house"),
+            ("people", "person"),
+            ("children", "child"),
+            ("status", "status"),
+            ("data", "data"),
+            ("class", "class"),
+            ("s", "s"),
+            ("user", "user"),
+        ];
+
+        for (plural, singular) in cases {
+            assert_eq!(singularize(plural), singular, "singularizing {:?}", plural);
+        }
+    }
+
+    #[test]
+    fn builds_type_names() {
+        let cases = [
+            ("details", false, "Details"),
+            ("billing_address", false, "BillingAddress"),
+            ("HTTPStatus", false, "HttpStatus"),
+            ("page-view", false, "PageView"),
+            ("employees", true, "Employee"),
+            ("order_items", true, "OrderItem"),
+            ("2fa", false, "V2fa"),
+            ("", false, "V"),
+            ("½", false, "V"),
+            ("self", false, "Self_"),
+        ];
+
+        for (name, singular, expected) in cases {
+            assert_eq!(type_name(name, singular), expected, "naming {:?}", name);
+
+            // Every name has to be a valid identifier
+            syn::parse_str::<Ident>(expected).unwrap();
+        }
+    }
+
+    #[test]
+    fn builds_field_idents() {
+        let cases = [
+            ("userId", "user_id"),
+            ("HTTPStatus", "http_status"),
+            ("first-name", "first_name"),
+            ("type", "r#type"),
+            ("2fa", "field_2fa"),
+            ("self", "self_"),
+            ("½", "field"),
+            ("", "field"),
+        ];
+
+        for (key, expected) in cases {
+            let field = field_ident(key, &mut HashSet::new());
+            assert_eq!(field.to_string(), expected, "naming {:?}", key);
+        }
+    }
+
+    #[test]
+    fn numbers_field_idents_that_clash() {
+        let mut taken = HashSet::new();
+
+        let fields =
+            ["a-b", "a_b", "aB", "a_b_2"].map(|key| field_ident(key, &mut taken).to_string());
+
+        assert_eq!(fields, ["a_b", "a_b_2", "a_b_3", "a_b_2_2"]);
+    }
+
+    #[test]
+    fn detects_key_styles() {
+        let fields = |keys: &[&str]| {
+            let mut taken = HashSet::new();
+            keys.iter()
+                .map(|key| (field_ident(key, &mut taken), key.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        let cases: &[(&[&str], Option<RenameStyle>)] = &[
+            (&["userId", "firstName"], Some(RenameStyle::Camel)),
+            (&["UserId", "FirstName"], Some(RenameStyle::Pascal)),
+            (&["user-id", "first-name"], Some(RenameStyle::Kebab)),
+            (
+                &["USER_ID", "FIRST_NAME"],
+                Some(RenameStyle::ScreamingSnake),
+            ),
+            (
+                &["USER-ID", "FIRST-NAME"],
+                Some(RenameStyle::ScreamingKebab),
+            ),
+            (&["user_id", "first_name"], None),
+            (&["userId", "first_name", "last-name"], None),
+            (&["HTTPStatus", "userId"], None),
+        ];
+
+        for (keys, style) in cases {
+            assert_eq!(
+                detect_key_style(&fields(keys)),
+                *style,
+                "detecting {:?}",
+                keys
+            );
+        }
+    }
+
+    #[test]
+    fn claims_free_names() {
+        let root = format_ident!("Rpc");
+        let mut names = Names::new(&root);
+
+        let claim = |names: &mut Names, name: &str| {
+            names.claim(&format_ident!("{}", name), &root).to_string()
+        };
+
+        assert_eq!(claim(&mut names, "Result"), "RpcResult");
+        assert_eq!(claim(&mut names, "Details"), "Details");
+        assert_eq!(claim(&mut names, "Details"), "RpcDetails");
+        assert_eq!(claim(&mut names, "Details"), "Details2");
+        assert_eq!(claim(&mut names, "Rpc"), "Rpc2");
+    }
+}
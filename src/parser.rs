@@ -1,31 +1,154 @@
+use std::path::PathBuf;
+
+use quote::format_ident;
 use syn::{
     braced,
     parse::{Parse, ParseStream},
-    Ident, Lit, Result, Token,
+    Ident, Lit, LitStr, Result, Token,
 };
 
+use crate::json_text;
+use crate::naming;
+
 #[derive(Debug, Default, Clone)]
 pub struct JsonMacroFlags {
     pub debug: bool,
-    pub rename_all: Option<RenameStyle>,
+    /// The style fields are serialized with, from `@camel`, `@rename_all(...)` or `@rename_serialize(...)`
+    pub rename_serialize: Option<RenameStyle>,
+    /// The style fields are deserialized from, from `@camel`, `@rename_all(...)` or `@rename_deserialize(...)`
+    pub rename_deserialize: Option<RenameStyle>,
     pub store_json_value: bool,
     pub use_serde_alias: bool,
     pub custom_derives: Vec<Ident>,
+    /// The preferred integer type from `@int(...)`, `i64` is used when it's not set
+    pub int_type: Option<Ident>,
+    /// Falls back to `serde_json::Value` instead of failing on conflicting types
+    pub lenient: bool,
+    /// Generates untagged enums for values of conflicting types
+    pub untagged: bool,
+    /// The field that tells apart the objects of an array, from `@tagged("type")`
+    pub tagged: Option<String>,
+    /// Visibility of every generated type, from `@pub`, `@pub(crate)` or `@vis(...)`
+    pub vis: Option<syn::Visibility>,
+    /// Visibility of every generated field, from `@field_vis(...)`
+    pub field_vis: Option<syn::Visibility>,
+    /// How structurally identical nested structs are shared, from `@dedupe(...)`
+    pub dedupe: DedupeStrategy,
+    /// Names for shared nested structs, `@share(Address)` or `@share(Address = "billing")`
+    pub share: Vec<(Ident, Option<String>)>,
+    /// Generates `fn sample()`, building the first sample as a struct literal, from `@sample`
+    pub sample: bool,
+    /// Generates `impl Default` for every struct from the sample values, from `@default_from_sample`
+    pub default_from_sample: bool,
+    /// Generates a `{Name}Builder` for every struct, from `@builder`
+    pub builder: bool,
+    /// Generates a `{Name}Field` enum and `get`/`set` for every struct, from `@fields`
+    pub fields: bool,
+    /// Generates the JSON Schema of the main struct, from `@json_schema`
+    pub json_schema: bool,
+    /// Prefixes nested type names with their parent's name, `CompanyDetails` instead of `Details`
+    pub prefix_nested: bool,
 }
 
-#[derive(Debug, Clone)]
+/// How nested objects with the same fields are turned into a single shared struct
+#[derive(Debug, Default, Clone)]
+pub enum DedupeStrategy {
+    /// Named after the words the keys have in common, `billing_address` and `shipping_address`
+    /// share `Address`, falling back to the first object's name
+    #[default]
+    Common,
+    /// Named after the first object
+    First,
+    /// Every object gets its own struct
+    Off,
+}
+
+/// Every `rename_all` style serde supports
+#[derive(Debug, Clone, PartialEq)]
 pub enum RenameStyle {
+    Lower,
+    Upper,
     Camel,
     Snake,
     Pascal,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameStyle {
+    const ALL: [RenameStyle; 8] = [
+        RenameStyle::Lower,
+        RenameStyle::Upper,
+        RenameStyle::Pascal,
+        RenameStyle::Camel,
+        RenameStyle::Snake,
+        RenameStyle::ScreamingSnake,
+        RenameStyle::Kebab,
+        RenameStyle::ScreamingKebab,
+    ];
+
+    /// Parses a style by the name serde gives it, like `"kebab-case"`.
+    pub fn from_serde_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|style| style.to_string() == name)
+    }
+
+    /// Renames a snake_case field the way `#[serde(rename_all = "...")]` does.
+    ///
+    /// # Parameters
+    /// - `field`: The snake_case field name, without `r#`
+    ///
+    /// # Returns
+    /// The name serde uses for the field in the JSON
+    pub fn apply(&self, field: &str) -> String {
+        match self {
+            RenameStyle::Lower | RenameStyle::Snake => field.to_string(),
+            RenameStyle::Upper | RenameStyle::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameStyle::Kebab => field.replace('_', "-"),
+            RenameStyle::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+            RenameStyle::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.extend(c.to_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+
+                pascal
+            }
+            RenameStyle::Camel => {
+                let pascal = RenameStyle::Pascal.apply(field);
+                let mut chars = pascal.chars();
+
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => pascal,
+                }
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for RenameStyle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            RenameStyle::Lower => f.write_str("lowercase"),
+            RenameStyle::Upper => f.write_str("UPPERCASE"),
             RenameStyle::Camel => f.write_str("camelCase"),
             RenameStyle::Snake => f.write_str("snake_case"),
             RenameStyle::Pascal => f.write_str("PascalCase"),
+            RenameStyle::ScreamingSnake => f.write_str("SCREAMING_SNAKE_CASE"),
+            RenameStyle::Kebab => f.write_str("kebab-case"),
+            RenameStyle::ScreamingKebab => f.write_str("SCREAMING-KEBAB-CASE"),
         }
     }
 }
@@ -34,74 +157,537 @@ impl std::fmt::Display for RenameStyle {
 pub struct JsonMacroInput {
     pub struct_name: Ident,
     pub flags: JsonMacroFlags,
+    /// The first sample, written inline or loaded from a file
     pub content: JsonStruct,
+    /// Every other sample, their shapes are unified with the first one
+    pub samples: Vec<JsonStruct>,
+    /// Absolute paths of the sample files loaded with `@file("...")`
+    pub source_files: Vec<PathBuf>,
 }
 
 impl Parse for JsonMacroInput {
     fn parse(input: ParseStream) -> Result<Self> {
         // Require struct name
         let struct_name = input.parse::<Ident>()?;
-        let mut flags = JsonMacroFlags::default();
-
-        while input.peek(Token![@]) {
-            input.parse::<Token![@]>()?;
-
-            let flag_ident = input.parse::<Ident>()?;
-
-            let flag_name = flag_ident.to_string();
-
-            match flag_name.as_str() {
-                "debug" => flags.debug = true,
-                "store_json" => flags.store_json_value = true,
-                "no_alias" => flags.use_serde_alias = false,
-                "camel" => flags.rename_all = Some(RenameStyle::Camel),
-                "snake" => flags.rename_all = Some(RenameStyle::Snake),
-                "pascal" => flags.rename_all = Some(RenameStyle::Pascal),
-                "derive" => {
-                    // Parse custom derives
-                    if input.peek(syn::token::Paren) {
-                        let content;
-                        syn::parenthesized!(content in input);
-
-                        let derives = content.parse_terminated(Ident::parse, Token![,])?;
-                        flags.custom_derives.extend(derives);
-                    } else {
-                        return Err(syn::Error::new(flag_ident.span(), "expected @derive(...)"));
-                    }
-                }
 
-                _ => {
-                    let message = format!("Unknown flag: {} Supported flags: @debug @camel @snake @pascal @store_json @no_alias @derive(...)", flag_name);
-                    return Err(input.error(&message));
-                }
+        // Samples given through `@samples([...])` and `@file(...)`
+        let (flags, samples, files) = parse_flags(input)?;
+
+        // Optional comma between the flags and the content, `json2struct!(User, r#"{...}"#)`
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        // Parse the struct content, it can be left out when the samples come from the flags
+        let mut all_samples = Vec::new();
+
+        if input.peek(LitStr) {
+            // Raw JSON text, so payloads can be pasted unchanged
+            let text = input.parse::<LitStr>()?;
+            all_samples.extend(parse_json_text(&text.value(), text.span())?);
+        } else if input.peek(syn::token::Brace) || (samples.is_empty() && files.is_empty()) {
+            let content;
+
+            braced!(content in input);
+
+            // The older `{{ ... }}` form wraps the object in an extra pair of braces
+            if content.peek(syn::token::Brace) {
+                all_samples.push(JsonStruct::parse(&content)?);
+            } else {
+                all_samples.push(JsonStruct::parse_entries(&content)?);
             }
         }
 
-        // Parse the struct content
+        all_samples.extend(samples);
 
-        let content;
+        // Samples loaded from files
+        let mut source_files = Vec::new();
 
-        braced!(content in input);
+        for path in &files {
+            let (source_file, file_samples) = load_json_file(path)?;
 
-        let json_struct = JsonStruct::parse(&content)?;
+            source_files.push(source_file);
+            all_samples.extend(file_samples);
+        }
+
+        let mut all_samples = all_samples.into_iter();
+
+        let content = all_samples.next().ok_or_else(|| {
+            syn::Error::new(struct_name.span(), "expected at least one sample object")
+        })?;
 
         Ok(JsonMacroInput {
             struct_name,
             flags,
-            content: json_struct,
+            content,
+            samples: all_samples.collect(),
+            source_files,
         })
     }
 }
 
+/// The input of `json2struct_schema!`, a JSON Schema file instead of samples
+#[derive(Debug, Clone)]
+pub struct SchemaMacroInput {
+    pub struct_name: Ident,
+    pub flags: JsonMacroFlags,
+    /// The schema document
+    pub schema: serde_json::Value,
+    /// The path of the schema as it was written, errors about the schema point at it
+    pub path: LitStr,
+    /// Absolute path of the schema file
+    pub source_file: PathBuf,
+}
+
+impl Parse for SchemaMacroInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        // The name can be left out, the schema's `title` is used then
+        let struct_name = if input.peek(Ident) {
+            Some(input.parse::<Ident>()?)
+        } else {
+            None
+        };
+
+        let (flags, samples, files) = parse_flags(input)?;
+
+        // A schema has no values to build or store
+        let sample_flag = [
+            ("@samples(...)", !samples.is_empty()),
+            ("@file(...)", !files.is_empty()),
+            ("@sample", flags.sample),
+            ("@default_from_sample", flags.default_from_sample),
+            ("@store_json", flags.store_json_value),
+        ]
+        .into_iter()
+        .find_map(|(flag, set)| set.then_some(flag));
+
+        if let Some(flag) = sample_flag {
+            return Err(input.error(format!(
+                "{} needs a sample, json2struct_schema! only reads a schema",
+                flag
+            )));
+        }
+
+        // Optional comma between the flags and the path, `json2struct_schema!(Order, "...")`
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        let path = input.parse::<LitStr>()?;
+        let (source_file, text) = read_manifest_file(&path)?;
+
+        let schema = serde_json::from_str::<serde_json::Value>(&text).map_err(|e| {
+            syn::Error::new(
+                path.span(),
+                format!("Invalid JSON: {} ({})", e, source_file.display()),
+            )
+        })?;
+
+        let struct_name = match struct_name {
+            Some(struct_name) => struct_name,
+            None => {
+                let title = schema
+                    .get("title")
+                    .and_then(serde_json::Value::as_str)
+                    .ok_or_else(|| {
+                        syn::Error::new(
+                            path.span(),
+                            "the schema has no `title`, name the struct: json2struct_schema!(Order, \"...\")",
+                        )
+                    })?;
+
+                format_ident!("{}", naming::type_name(title, false), span = path.span())
+            }
+        };
+
+        Ok(SchemaMacroInput {
+            struct_name,
+            flags,
+            schema,
+            path,
+            source_file,
+        })
+    }
+}
+
+/// Parses the flags before the content, `@debug @camel @derive(PartialEq)`.
+///
+/// # Returns
+/// A tuple containing:
+/// 1. The flags
+/// 2. The samples given with `@samples([...])`
+/// 3. The paths given with `@file(...)`
+fn parse_flags(input: ParseStream) -> Result<(JsonMacroFlags, Vec<JsonStruct>, Vec<LitStr>)> {
+    let mut flags = JsonMacroFlags::default();
+    let mut samples = Vec::new();
+    let mut files = Vec::new();
+
+    while input.peek(Token![@]) {
+        input.parse::<Token![@]>()?;
+
+        // `pub` is a keyword, so `@pub` and `@pub(crate)` are parsed as a visibility
+        if input.peek(Token![pub]) {
+            flags.vis = Some(input.parse::<syn::Visibility>()?);
+            continue;
+        }
+
+        let flag_ident = input.parse::<Ident>()?;
+
+        let flag_name = flag_ident.to_string();
+
+        match flag_name.as_str() {
+            "debug" => flags.debug = true,
+            "store_json" => flags.store_json_value = true,
+            "lenient" => flags.lenient = true,
+            "untagged" => flags.untagged = true,
+            "prefix_nested" => flags.prefix_nested = true,
+            "sample" => flags.sample = true,
+            "default_from_sample" => flags.default_from_sample = true,
+            "builder" => flags.builder = true,
+            "fields" => flags.fields = true,
+            "json_schema" => flags.json_schema = true,
+            "alias" => flags.use_serde_alias = true,
+            "no_alias" => flags.use_serde_alias = false,
+            "camel" | "snake" | "pascal" => {
+                let style = match flag_name.as_str() {
+                    "camel" => RenameStyle::Camel,
+                    "snake" => RenameStyle::Snake,
+                    _ => RenameStyle::Pascal,
+                };
+
+                flags.rename_serialize = Some(style.clone());
+                flags.rename_deserialize = Some(style);
+            }
+            "rename_all" | "rename_serialize" | "rename_deserialize" => {
+                // Parse a serde rename style, `@rename_all("kebab-case")`
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let name = content.parse::<LitStr>()?;
+
+                    let style = RenameStyle::from_serde_name(&name.value()).ok_or_else(|| {
+                        syn::Error::new(
+                            name.span(),
+                            format!(
+                                "unknown rename style, expected one of: {}",
+                                RenameStyle::ALL
+                                    .iter()
+                                    .map(|style| format!("\"{}\"", style))
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ),
+                        )
+                    })?;
+
+                    if flag_name != "rename_deserialize" {
+                        flags.rename_serialize = Some(style.clone());
+                    }
+                    if flag_name != "rename_serialize" {
+                        flags.rename_deserialize = Some(style);
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        flag_ident.span(),
+                        format!("expected @{}(\"kebab-case\")", flag_name),
+                    ));
+                }
+            }
+            "derive" => {
+                // Parse custom derives
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let derives = content.parse_terminated(Ident::parse, Token![,])?;
+                    flags.custom_derives.extend(derives);
+                } else {
+                    return Err(syn::Error::new(flag_ident.span(), "expected @derive(...)"));
+                }
+            }
+            "int" => {
+                // Parse the default integer type
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let int_type = content.parse::<Ident>()?;
+
+                    if integer_range(&int_type.to_string()).is_none() {
+                        return Err(syn::Error::new(
+                            int_type.span(),
+                            "expected an integer type, like @int(i32) or @int(u64)",
+                        ));
+                    }
+
+                    flags.int_type = Some(int_type);
+                } else {
+                    return Err(syn::Error::new(flag_ident.span(), "expected @int(type)"));
+                }
+            }
+            "file" => {
+                // Parse the sample file paths
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let paths = content.parse_terminated(<LitStr as Parse>::parse, Token![,])?;
+                    files.extend(paths);
+                } else {
+                    return Err(syn::Error::new(
+                        flag_ident.span(),
+                        "expected @file(\"path/to/sample.json\")",
+                    ));
+                }
+            }
+            "vis" | "field_vis" => {
+                // Parse the visibility, `@vis(pub(crate))`
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let vis = Some(content.parse::<syn::Visibility>()?);
+
+                    if flag_name == "vis" {
+                        flags.vis = vis;
+                    } else {
+                        flags.field_vis = vis;
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        flag_ident.span(),
+                        format!("expected @{}(pub)", flag_name),
+                    ));
+                }
+            }
+            "dedupe" => {
+                // Parse the naming strategy of shared structs
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let strategy = content.parse::<Ident>()?;
+
+                    flags.dedupe = match strategy.to_string().as_str() {
+                        "common" => DedupeStrategy::Common,
+                        "first" => DedupeStrategy::First,
+                        "off" => DedupeStrategy::Off,
+                        _ => {
+                            return Err(syn::Error::new(
+                                strategy.span(),
+                                "expected @dedupe(common), @dedupe(first) or @dedupe(off)",
+                            ))
+                        }
+                    };
+                } else {
+                    return Err(syn::Error::new(
+                        flag_ident.span(),
+                        "expected @dedupe(common|first|off)",
+                    ));
+                }
+            }
+            "share" => {
+                // Parse the shared struct names, `@share(Address, Geo = "location")`
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    while !content.is_empty() {
+                        let name = content.parse::<Ident>()?;
+
+                        let key = if content.peek(Token![=]) {
+                            content.parse::<Token![=]>()?;
+                            Some(content.parse::<LitStr>()?.value())
+                        } else {
+                            None
+                        };
+
+                        flags.share.push((name, key));
+
+                        if content.peek(Token![,]) {
+                            content.parse::<Token![,]>()?;
+                        }
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        flag_ident.span(),
+                        "expected @share(Name) or @share(Name = \"key\")",
+                    ));
+                }
+            }
+            "tagged" => {
+                // Parse the tag field name
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    flags.tagged = Some(content.parse::<LitStr>()?.value());
+                } else {
+                    return Err(syn::Error::new(
+                        flag_ident.span(),
+                        "expected @tagged(\"field\")",
+                    ));
+                }
+            }
+            "samples" => {
+                // Parse an array of sample objects
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+
+                    let span = content.span();
+
+                    match parse_json_value(&content)? {
+                        JsonValue::Array(values) => {
+                            for value in values {
+                                match value {
+                                    JsonValue::Object(entries) => {
+                                        samples.push(JsonStruct { entries })
+                                    }
+                                    _ => {
+                                        return Err(syn::Error::new(
+                                            span,
+                                            "every sample in @samples([...]) must be an object",
+                                        ))
+                                    }
+                                }
+                            }
+                        }
+                        _ => return Err(syn::Error::new(span, "expected @samples([{...}, ...])")),
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        flag_ident.span(),
+                        "expected @samples([{...}, ...])",
+                    ));
+                }
+            }
+
+            _ => {
+                let message = format!("Unknown flag: {} Supported flags: @debug @camel @snake @pascal @rename_all(...) @rename_serialize(...) @rename_deserialize(...) @store_json @sample @default_from_sample @builder @fields @json_schema @alias @no_alias @derive(...) @file(...) @samples(...) @int(...) @lenient @untagged @tagged(...) @pub @vis(...) @field_vis(...) @dedupe(...) @share(...) @prefix_nested", flag_name);
+                return Err(input.error(&message));
+            }
+        }
+    }
+
+    Ok((flags, samples, files))
+}
+
+/// Returns the inclusive range of values an integer type can hold, or `None` if it's not one
+pub fn integer_range(int_type: &str) -> Option<(i128, i128)> {
+    let range = match int_type {
+        "i8" => (i8::MIN as i128, i8::MAX as i128),
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" | "isize" => (i64::MIN as i128, i64::MAX as i128),
+        "i128" => (i128::MIN, i128::MAX),
+        "u8" => (0, u8::MAX as i128),
+        "u16" => (0, u16::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        "u64" | "usize" => (0, u64::MAX as i128),
+        "u128" => (0, i128::MAX),
+        _ => return None,
+    };
+
+    Some(range)
+}
+
+/// A JSON number, integers and floats are kept apart so they get their own types
+#[derive(Debug, Clone)]
+pub enum JsonNumber {
+    /// The value, and the number as it was written (`-0`) so it round-trips exactly
+    Int(i128, String),
+    /// The value, and the number as it was written (`-0.25`, `1e-9`) so it round-trips exactly
+    Float(f64, String),
+}
+
+impl JsonNumber {
+    /// Parses the text of a JSON number, integers become `Int` as long as they fit in an `i128`
+    pub fn from_text(text: &str) -> Option<Self> {
+        let is_float = text.contains(['.', 'e', 'E']);
+
+        if !is_float {
+            if let Ok(i) = text.parse::<i128>() {
+                return Some(JsonNumber::Int(i, text.to_string()));
+            }
+        }
+
+        let value = text.parse::<f64>().ok()?;
+
+        Some(JsonNumber::Float(value, text.to_string()))
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            JsonNumber::Int(i, _) => *i as f64,
+            JsonNumber::Float(f, _) => *f,
+        }
+    }
+
+    /// Writes the number as JSON, as it was written when that's valid JSON (`-0`, `1e-9`, not `1.`)
+    ///
+    /// # Errors
+    /// If the number is too big for an `f64`, JSON parsers can't read it back
+    pub fn to_json_text(&self) -> std::result::Result<String, String> {
+        match self {
+            JsonNumber::Int(_, text) if is_json_number(text) => Ok(text.clone()),
+            JsonNumber::Int(i, _) => Ok(i.to_string()),
+            JsonNumber::Float(f, text) if !f.is_finite() => Err(format!(
+                "`{}` is too big for an f64, it can't be stored as JSON",
+                text
+            )),
+            JsonNumber::Float(_, text) if is_json_number(text) => Ok(text.clone()),
+            JsonNumber::Float(f, _) => Ok(format!("{:?}", f)),
+        }
+    }
+}
+
+/// Checks that a number follows the JSON grammar, `-0.5e10` but not `.5`, `1.` or `0x10`
+pub fn is_json_number(text: &str) -> bool {
+    let digits = |s: &str| s.bytes().take_while(u8::is_ascii_digit).count();
+
+    let rest = text.strip_prefix('-').unwrap_or(text);
+
+    // Integer part, no leading zeros
+    let int_len = digits(rest);
+    if int_len == 0 || (int_len > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &rest[int_len..];
+
+    // Fraction
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = digits(fraction);
+        if len == 0 {
+            return false;
+        }
+        rest = &fraction[len..];
+    }
+
+    // Exponent
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let len = digits(exponent);
+        if len == 0 {
+            return false;
+        }
+        rest = &exponent[len..];
+    }
+
+    rest.is_empty()
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum JsonValue {
     Str(String),
-    Number(f64),
+    Number(JsonNumber),
     Boolean(bool),
     Null,
     Array(Vec<JsonValue>),
     Object(Vec<(String, JsonValue)>),
+    /// A value with a type annotation, like `8080 as u16` or `null as String`
+    Annotated(Box<JsonValue>, Box<syn::Type>),
 }
 
 #[allow(dead_code)]
@@ -116,7 +702,7 @@ impl JsonValue {
 
     pub fn as_number(&self) -> Option<f64> {
         match self {
-            JsonValue::Number(n) => Some(*n),
+            JsonValue::Number(n) => Some(n.as_f64()),
             _ => None,
         }
     }
@@ -142,27 +728,80 @@ impl JsonValue {
         }
     }
 
-    // Convert to serde_json::Value
-    pub fn to_serde_value(&self) -> serde_json::Value {
-        match self {
-            JsonValue::Str(s) => serde_json::Value::String(s.clone()),
-            JsonValue::Number(n) => serde_json::Value::Number(
-                serde_json::Number::from_f64(*n).unwrap_or(serde_json::Number::from(0)),
+    // Convert from serde_json::Value
+    pub fn from_serde_value(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(s) => JsonValue::Str(s.clone()),
+            serde_json::Value::Number(n) => JsonValue::Number(
+                JsonNumber::from_text(&n.to_string())
+                    .unwrap_or_else(|| JsonNumber::Float(f64::NAN, n.to_string())),
             ),
-            JsonValue::Boolean(b) => serde_json::Value::Bool(*b),
-            JsonValue::Null => serde_json::Value::Null,
-            JsonValue::Array(arr) => {
-                serde_json::Value::Array(arr.iter().map(|v| v.to_serde_value()).collect())
+            serde_json::Value::Bool(b) => JsonValue::Boolean(*b),
+            serde_json::Value::Null => JsonValue::Null,
+            serde_json::Value::Array(arr) => {
+                JsonValue::Array(arr.iter().map(JsonValue::from_serde_value).collect())
             }
-            JsonValue::Object(obj) => {
-                let map = obj
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v.to_serde_value()))
-                    .collect();
-                serde_json::Value::Object(map)
+            serde_json::Value::Object(obj) => JsonValue::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), JsonValue::from_serde_value(v)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Writes the value as compact JSON text.
+    ///
+    /// Keys stay in the order they were written and numbers keep their form,
+    /// so the text matches the input byte for byte, whitespace aside.
+    ///
+    /// # Errors
+    /// If a number can't be written as JSON
+    pub fn write_json(&self, out: &mut String) -> std::result::Result<(), String> {
+        match self {
+            JsonValue::Str(s) => out.push_str(&json_string(s)),
+            JsonValue::Number(n) => out.push_str(&n.to_json_text()?),
+            JsonValue::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+            JsonValue::Null => out.push_str("null"),
+            JsonValue::Annotated(value, _) => value.write_json(out)?,
+            JsonValue::Array(arr) => {
+                out.push('[');
+                for (i, value) in arr.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    value.write_json(out)?;
+                }
+                out.push(']');
             }
+            JsonValue::Object(obj) => write_json_object(obj, out)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the entries of an object as JSON, in order
+fn write_json_object(
+    entries: &[(String, JsonValue)],
+    out: &mut String,
+) -> std::result::Result<(), String> {
+    out.push('{');
+    for (i, (key, value)) in entries.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
         }
+        out.push_str(&json_string(key));
+        out.push(':');
+        value.write_json(out)?;
     }
+    out.push('}');
+
+    Ok(())
+}
+
+/// Quotes and escapes a string the way serde_json does
+fn json_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
 }
 
 #[derive(Debug, Clone)]
@@ -175,6 +814,20 @@ impl Parse for JsonStruct {
         let content;
         braced!(content in input);
 
+        JsonStruct::parse_entries(&content)
+    }
+}
+
+impl JsonStruct {
+    /// Writes the object as compact JSON text, see [`JsonValue::write_json`]
+    pub fn to_json_text(&self) -> std::result::Result<String, String> {
+        let mut out = String::new();
+        write_json_object(&self.entries, &mut out)?;
+        Ok(out)
+    }
+
+    /// Parses the `"key": value` entries of an object whose braces were already consumed
+    pub fn parse_entries(content: ParseStream) -> Result<Self> {
         let mut entries = Vec::new();
 
         while !content.is_empty() {
@@ -185,11 +838,15 @@ impl Parse for JsonStruct {
                 _ => return Err(content.error("Key must be a string")),
             };
 
-            // Parse arrow
-            content.parse::<Token![:]>()?;
+            // Parse the separator, either `:` or `=>`
+            if content.peek(Token![=>]) {
+                content.parse::<Token![=>]>()?;
+            } else {
+                content.parse::<Token![:]>()?;
+            }
 
             // Parse value
-            let value = parse_json_value(&content)?;
+            let value = parse_json_value(content)?;
 
             entries.push((key_str, value));
 
@@ -204,6 +861,24 @@ impl Parse for JsonStruct {
 }
 
 pub fn parse_json_value(input: ParseStream) -> Result<JsonValue> {
+    let value = parse_plain_value(input)?;
+
+    // Optional type annotation, `8080 as u16` or `8080: u16`
+    if input.peek(Token![as]) {
+        input.parse::<Token![as]>()?;
+    } else if input.peek(Token![:]) && !input.peek(Token![::]) {
+        input.parse::<Token![:]>()?;
+    } else {
+        return Ok(value);
+    }
+
+    let ty = input.parse::<syn::Type>()?;
+
+    Ok(JsonValue::Annotated(Box::new(value), Box::new(ty)))
+}
+
+/// Parses a JSON value without its type annotation
+fn parse_plain_value(input: ParseStream) -> Result<JsonValue> {
     if input.peek(syn::token::Bracket) {
         // Parse array
         let content;
@@ -227,13 +902,159 @@ pub fn parse_json_value(input: ParseStream) -> Result<JsonValue> {
         return Ok(JsonValue::Object(nested.entries));
     }
 
+    // `null` is an identifier, not a literal
+    if input.peek(Ident) {
+        let ident = input.parse::<Ident>()?;
+
+        return if ident == "null" {
+            Ok(JsonValue::Null)
+        } else {
+            Err(syn::Error::new(ident.span(), "expected a JSON value"))
+        };
+    }
+
+    // Negative numbers are a `-` followed by the literal
+    let sign = if input.peek(Token![-]) {
+        input.parse::<Token![-]>()?;
+        "-"
+    } else {
+        ""
+    };
+
     // Parse literal values
     let lit: Lit = input.parse()?;
     match lit {
-        Lit::Str(s) => Ok(JsonValue::Str(s.value())),
-        Lit::Int(i) => Ok(JsonValue::Number(i.base10_parse::<f64>()?)),
-        Lit::Float(f) => Ok(JsonValue::Number(f.base10_parse::<f64>()?)),
-        Lit::Bool(b) => Ok(JsonValue::Boolean(b.value)),
+        Lit::Str(s) if sign.is_empty() => Ok(JsonValue::Str(s.value())),
+        Lit::Bool(b) if sign.is_empty() => Ok(JsonValue::Boolean(b.value)),
+
+        // The digits are kept as written, exponents like `1e-9` included
+        Lit::Int(ref n) if n.suffix().is_empty() => parse_number(sign, n.base10_digits(), &lit),
+        Lit::Float(ref n) if n.suffix().is_empty() => parse_number(sign, n.base10_digits(), &lit),
+
+        Lit::Int(_) | Lit::Float(_) => Err(syn::Error::new(
+            lit.span(),
+            "Number suffixes aren't supported, annotate the value instead, like `8080 as u16`",
+        )),
+        _ if !sign.is_empty() => Err(syn::Error::new(lit.span(), "expected a number after `-`")),
         _ => Err(input.error("Unsupported literal type")),
     }
 }
+
+/// Builds a JSON number from its sign and digits
+fn parse_number(sign: &str, digits: &str, lit: &Lit) -> Result<JsonValue> {
+    JsonNumber::from_text(&format!("{}{}", sign, digits))
+        .map(JsonValue::Number)
+        .ok_or_else(|| syn::Error::new(lit.span(), "Invalid number"))
+}
+
+/// Reads and parses a JSON sample file, resolved relative to `CARGO_MANIFEST_DIR`.
+///
+/// # Returns
+/// The absolute path of the file (so the caller can track it for rebuilds)
+/// and the samples it holds
+fn load_json_file(path: &LitStr) -> Result<(PathBuf, Vec<JsonStruct>)> {
+    let (full_path, text) = read_manifest_file(path)?;
+
+    let samples = parse_json_text(&text, path.span())
+        .map_err(|e| syn::Error::new(path.span(), format!("{} ({})", e, full_path.display())))?;
+
+    Ok((full_path, samples))
+}
+
+/// Reads a file, resolved relative to `CARGO_MANIFEST_DIR`.
+///
+/// # Returns
+/// The absolute path of the file and its text
+fn read_manifest_file(path: &LitStr) -> Result<(PathBuf, String)> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(path.span(), "CARGO_MANIFEST_DIR is not set"))?;
+
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+
+    let text = std::fs::read_to_string(&full_path).map_err(|e| {
+        syn::Error::new(
+            path.span(),
+            format!("Couldn't read {}: {}", full_path.display(), e),
+        )
+    })?;
+
+    Ok((full_path, text))
+}
+
+/// Parses real JSON text into the samples it holds.
+///
+/// The top-level value is either one object, or an array of objects that are all samples.
+fn parse_json_text(text: &str, span: proc_macro2::Span) -> Result<Vec<JsonStruct>> {
+    let value =
+        json_text::read(text).map_err(|e| syn::Error::new(span, format!("Invalid JSON: {}", e)))?;
+
+    let not_an_object = || {
+        syn::Error::new(
+            span,
+            "The top-level JSON value must be an object or an array of objects",
+        )
+    };
+
+    match value {
+        JsonValue::Object(entries) => Ok(vec![JsonStruct { entries }]),
+        JsonValue::Array(values) if !values.is_empty() => values
+            .into_iter()
+            .map(|value| match value {
+                JsonValue::Object(entries) => Ok(JsonStruct { entries }),
+                _ => Err(not_an_object()),
+            })
+            .collect(),
+        _ => Err(not_an_object()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_text_of_numbers() {
+        let cases = [
+            ("0", "0"),
+            ("-0", "-0"),
+            ("42", "42"),
+            ("-17", "-17"),
+            (
+                "170141183460469231731687303715884105728",
+                "170141183460469231731687303715884105728",
+            ),
+            ("-0.0", "-0.0"),
+            ("0.5", "0.5"),
+            ("1e-9", "1e-9"),
+            ("1E+2", "1E+2"),
+            ("1.", "1.0"),
+        ];
+
+        for (text, json) in cases {
+            let number = JsonNumber::from_text(text).unwrap();
+            assert_eq!(
+                number.to_json_text(),
+                Ok(json.to_string()),
+                "writing {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn tells_integers_from_floats() {
+        assert!(matches!(
+            JsonNumber::from_text("-0"),
+            Some(JsonNumber::Int(0, _))
+        ));
+        assert!(matches!(
+            JsonNumber::from_text("1e2"),
+            Some(JsonNumber::Float(..))
+        ));
+        assert!(matches!(
+            JsonNumber::from_text("2.0"),
+            Some(JsonNumber::Float(..))
+        ));
+        assert!(JsonNumber::from_text("abc").is_none());
+    }
+}
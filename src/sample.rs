@@ -0,0 +1,714 @@
+use std::collections::{HashMap, HashSet};
+
+use quote::{format_ident, quote, ToTokens};
+use syn::Ident;
+
+use crate::generator::{element_path, field_path, integer_type, variant_path, GeneratedStruct};
+use crate::naming;
+use crate::parser::{integer_range, JsonMacroFlags, JsonNumber, JsonStruct, JsonValue};
+use crate::shape::{FieldShape, Shape, TaggedVariant};
+
+/// Generates `fn sample()` for the main struct, returning the sample as a struct literal.
+///
+/// `Example`
+///
+/// ```rust,ignore
+/// impl User {
+///     pub fn sample() -> Self {
+///         User {
+///             name: ::std::string::String::from("John"),
+///             age: 30i64,
+///         }
+///     }
+/// }
+/// ```
+///
+/// # Parameters
+/// - `struct_name`: The name of the main struct
+/// - `content`: The sample to build
+/// - `types`: The names of the generated types, by the path of the values they hold
+/// - `structs`: Every generated struct
+/// - `flags`: The macro flags
+///
+/// # Errors
+/// If a value can't be built as the type generated for it
+pub fn sample_impl(
+    struct_name: &Ident,
+    content: &JsonStruct,
+    types: &HashMap<String, Ident>,
+    structs: &[GeneratedStruct],
+    flags: &JsonMacroFlags,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let builder = Builder::new("@sample", types, structs, flags);
+
+    let value = builder
+        .object_expr(struct_name, &content.entries)
+        .map_err(|message| syn::Error::new(struct_name.span(), message))?;
+
+    Ok(quote! {
+        impl #struct_name {
+            /// The sample this type was generated from
+            pub fn sample() -> Self {
+                #value
+            }
+        }
+    })
+}
+
+/// Generates `impl Default` for every struct, returning the first value seen for it in the samples.
+///
+/// # Parameters
+/// - `struct_name`: The name of the main struct
+/// - `samples`: Every sample, in order
+/// - `types`: The names of the generated types, by the path of the values they hold
+/// - `structs`: Every generated struct
+/// - `flags`: The macro flags
+///
+/// # Errors
+/// If a value can't be built as the type generated for it
+pub fn default_impls(
+    struct_name: &Ident,
+    samples: &[&JsonStruct],
+    types: &HashMap<String, Ident>,
+    structs: &[GeneratedStruct],
+    flags: &JsonMacroFlags,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let builder = Builder::new("@default_from_sample", types, structs, flags);
+
+    let root = structs
+        .iter()
+        .find(|generated| generated.name == *struct_name)
+        .map(|generated| Shape::Object(generated.fields.clone()))
+        .unwrap_or(Shape::Unknown);
+
+    // Every object in the samples, with the path it's at
+    let mut objects = Vec::new();
+    for sample in samples {
+        let value = JsonValue::Object(sample.entries.clone());
+        builder.collect_objects(&value, &root, "", &mut objects);
+    }
+
+    let mut impls = Vec::new();
+
+    for generated in structs {
+        let name = &generated.name;
+
+        // Every struct comes from at least one object, shared structs from several
+        let Some((_, entries)) = objects
+            .iter()
+            .find(|(path, _)| types.get(path) == Some(name))
+        else {
+            continue;
+        };
+
+        let value = builder
+            .object_expr(name, entries)
+            .map_err(|message| syn::Error::new(struct_name.span(), message))?;
+
+        impls.push(quote! {
+            impl ::std::default::Default for #name {
+                /// The first value seen for this type in the samples
+                fn default() -> Self {
+                    #value
+                }
+            }
+        });
+    }
+
+    Ok(impls)
+}
+
+/// Builds Rust expressions from JSON values, following the types generated for them
+struct Builder<'a> {
+    /// The flag the values are built for, named in errors
+    flag: &'static str,
+    types: &'a HashMap<String, Ident>,
+    structs: HashMap<&'a Ident, &'a GeneratedStruct>,
+    flags: &'a JsonMacroFlags,
+}
+
+impl<'a> Builder<'a> {
+    fn new(
+        flag: &'static str,
+        types: &'a HashMap<String, Ident>,
+        structs: &'a [GeneratedStruct],
+        flags: &'a JsonMacroFlags,
+    ) -> Self {
+        let structs = structs
+            .iter()
+            .map(|generated| (&generated.name, generated))
+            .collect();
+
+        Builder {
+            flag,
+            types,
+            structs,
+            flags,
+        }
+    }
+
+    /// The name of the type generated for the values at a path
+    fn type_at(&self, path: &str) -> Result<&'a Ident, String> {
+        self.types
+            .get(path)
+            .ok_or_else(|| format!("{}: no type was generated for `{}`", self.flag, path))
+    }
+
+    /// Builds a value of a generated struct.
+    ///
+    /// A struct shared by several objects is generated once,
+    /// so the types nested in it are looked up from where it was generated.
+    fn object_expr(
+        &self,
+        struct_name: &Ident,
+        entries: &[(String, JsonValue)],
+    ) -> Result<proc_macro2::TokenStream, String> {
+        let generated = self.structs.get(struct_name).ok_or_else(|| {
+            format!(
+                "{}: the struct `{}` wasn't generated",
+                self.flag, struct_name
+            )
+        })?;
+
+        self.struct_expr(struct_name, &generated.fields, entries, &generated.path)
+    }
+
+    /// Records every object in a value with the path of the type generated for it, following its shape
+    fn collect_objects(
+        &self,
+        value: &JsonValue,
+        shape: &Shape,
+        path: &str,
+        out: &mut Vec<(String, Vec<(String, JsonValue)>)>,
+    ) {
+        match (shape, value) {
+            (_, JsonValue::Annotated(value, _)) => self.collect_objects(value, shape, path, out),
+            (Shape::Optional(inner), value) => self.collect_objects(value, inner, path, out),
+            (Shape::Array(inner), JsonValue::Array(items)) => {
+                for item in items {
+                    self.collect_objects(item, inner, &element_path(path), out);
+                }
+            }
+            (Shape::Object(fields), JsonValue::Object(entries)) => {
+                out.push((path.to_string(), entries.clone()));
+
+                // The types nested in a shared struct are found from where it was generated
+                let path = self
+                    .types
+                    .get(path)
+                    .and_then(|name| self.structs.get(name))
+                    .map_or(path, |generated| generated.path.as_str());
+
+                for field in fields {
+                    if let Some((_, value)) = entries.iter().find(|(key, _)| *key == field.key) {
+                        let field_path = field_path(path, &field.key);
+                        self.collect_objects(value, &field.shape, &field_path, out);
+                    }
+                }
+            }
+            (Shape::Union(members), value) => {
+                if let Some((variant_name, member)) = union_member(members, value) {
+                    let member_path = variant_path(path, variant_name);
+                    self.collect_objects(value, member, &member_path, out);
+                }
+            }
+            (Shape::Tagged { tag, variants }, JsonValue::Object(entries)) => {
+                if let Some(variant) = tagged_variant(tag, variants, entries) {
+                    let entries = entries
+                        .iter()
+                        .filter(|(key, _)| key != tag)
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    let fields = Shape::Object(variant.fields.clone());
+                    let value = JsonValue::Object(entries);
+                    let variant_path = variant_path(path, &variant.tag_value);
+
+                    self.collect_objects(&value, &fields, &variant_path, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Builds a struct literal, fields missing from the object are `None`
+    fn struct_expr(
+        &self,
+        struct_name: &Ident,
+        fields: &[FieldShape],
+        entries: &[(String, JsonValue)],
+        path: &str,
+    ) -> Result<proc_macro2::TokenStream, String> {
+        // Field names are given in the same order as when the struct was generated
+        let mut taken = HashSet::new();
+        let mut values = Vec::new();
+
+        for field in fields {
+            let field_name = naming::field_ident(&field.key, &mut taken);
+            let value = entries.iter().find(|(key, _)| *key == field.key);
+
+            let value = match value {
+                None => quote!(::std::option::Option::None),
+                Some((_, value)) => {
+                    let expr =
+                        self.value_expr(value, &field.shape, &field_path(path, &field.key))?;
+
+                    // Fields missing from other samples are wrapped in an `Option`
+                    match field.shape {
+                        Shape::Null | Shape::Optional(_) => expr,
+                        _ if field.missing => quote!(::std::option::Option::Some(#expr)),
+                        _ => expr,
+                    }
+                }
+            };
+
+            values.push(quote!(#field_name: #value));
+        }
+
+        Ok(quote!(#struct_name { #(#values),* }))
+    }
+
+    /// Builds a value of the type generated for a shape
+    fn value_expr(
+        &self,
+        value: &JsonValue,
+        shape: &Shape,
+        path: &str,
+    ) -> Result<proc_macro2::TokenStream, String> {
+        let expr = match (shape, value) {
+            // The annotation is already part of the shape
+            (_, JsonValue::Annotated(value, _)) => self.value_expr(value, shape, path)?,
+
+            (Shape::Null | Shape::Optional(_), JsonValue::Null) => {
+                quote!(::std::option::Option::None)
+            }
+            (Shape::Optional(inner), value) => {
+                let expr = self.value_expr(value, inner, path)?;
+                quote!(::std::option::Option::Some(#expr))
+            }
+
+            (Shape::Str, JsonValue::Str(s)) => quote!(::std::string::String::from(#s)),
+            (Shape::Bool, JsonValue::Boolean(b)) => quote!(#b),
+            (Shape::Int { min, max }, JsonValue::Number(number @ JsonNumber::Int(..))) => {
+                let int_type = integer_type(*min, *max, self.flags)
+                    .expect("integer shapes always have a type")
+                    .to_string();
+                number_literal(number, &int_type)
+            }
+            (Shape::Float, JsonValue::Number(number)) if is_finite_as(number, "f64") => {
+                number_literal(number, "f64")
+            }
+
+            (Shape::Array(inner), JsonValue::Array(items)) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.value_expr(item, inner, &element_path(path)))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                quote!(::std::vec![#(#items),*])
+            }
+
+            (Shape::Object(_), JsonValue::Object(entries)) => {
+                self.object_expr(self.type_at(path)?, entries)?
+            }
+
+            // Untagged enums have one variant per kind of value
+            (Shape::Union(members), value) => {
+                let enum_name = self.type_at(path)?;
+
+                let (variant_name, member) = union_member(members, value).ok_or_else(|| {
+                    format!("{}: no variant fits the value at `{}`", self.flag, path)
+                })?;
+
+                let expr = self.value_expr(value, member, &variant_path(path, variant_name))?;
+                let variant_name = format_ident!("{}", variant_name);
+
+                quote!(#enum_name::#variant_name(#expr))
+            }
+
+            // Tagged enums have one variant per tag value, the tag itself isn't a field
+            (Shape::Tagged { tag, variants }, JsonValue::Object(entries)) => {
+                let enum_name = self.type_at(path)?;
+
+                let variant = tagged_variant(tag, variants, entries).ok_or_else(|| {
+                    format!("{}: no variant fits the value at `{}`", self.flag, path)
+                })?;
+
+                let variant_path = variant_path(path, &variant.tag_value);
+                let variant_name = format_ident!("{}", variant.name);
+
+                let entries = entries
+                    .iter()
+                    .filter(|(key, _)| key != tag)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let expr = self.object_expr(self.type_at(&variant_path)?, &entries)?;
+
+                quote!(#enum_name::#variant_name(#expr))
+            }
+
+            (Shape::Explicit(ty), value) => explicit_expr(value, ty, path, self.flag)?,
+
+            // Conflicting types with `@lenient`, and nested empty arrays
+            (Shape::Any | Shape::Unknown, value) => json_value_expr(value),
+
+            _ => {
+                return Err(format!(
+                    "{}: the value at `{}` doesn't fit its type",
+                    self.flag, path
+                ))
+            }
+        };
+
+        Ok(expr)
+    }
+}
+
+/// Picks the member of an untagged enum a value belongs to, with the name of its variant
+fn union_member<'s>(members: &'s [Shape], value: &JsonValue) -> Option<(&'static str, &'s Shape)> {
+    members.iter().find_map(|member| {
+        let variant_name = match (member, value) {
+            (Shape::Str, JsonValue::Str(_)) => "Str",
+            (Shape::Int { .. } | Shape::Float, JsonValue::Number(_)) => "Num",
+            (Shape::Bool, JsonValue::Boolean(_)) => "Bool",
+            (Shape::Object(_), JsonValue::Object(_)) => "Obj",
+            (Shape::Array(_), JsonValue::Array(_)) => "Arr",
+            _ => return None,
+        };
+
+        Some((variant_name, member))
+    })
+}
+
+/// Picks the variant of a tagged enum an object belongs to, from its tag field
+fn tagged_variant<'s>(
+    tag: &str,
+    variants: &'s [TaggedVariant],
+    entries: &[(String, JsonValue)],
+) -> Option<&'s TaggedVariant> {
+    entries.iter().find_map(|(key, value)| match value {
+        JsonValue::Str(tag_value) if key == tag => variants
+            .iter()
+            .find(|variant| variant.tag_value == *tag_value),
+        _ => None,
+    })
+}
+
+/// Builds a value of a type given in the input.
+///
+/// Numbers, booleans, chars and strings are written as literals, and `Option`, `Vec`, `Box`, maps,
+/// tuples and arrays are built from their values, `[] as Vec<String>` is `::std::vec![]`.
+/// Other types are deserialized from a `serde_json::Value` when the value is built, `"..." as uuid::Uuid`.
+///
+/// # Parameters
+/// - `value`: The value
+/// - `ty`: The type it's annotated with
+/// - `path`: Where the value is, see [`field_path`]
+/// - `flag`: The flag the value is built for, named in errors
+///
+/// # Errors
+/// If the value doesn't fit a type with a literal form, `"abc" as u16`,
+/// or the type can't be deserialized, `"..." as &str`
+fn explicit_expr(
+    value: &JsonValue,
+    ty: &syn::Type,
+    path: &str,
+    flag: &str,
+) -> Result<proc_macro2::TokenStream, String> {
+    let mismatch = || {
+        format!(
+            "{}: the value at `{}` doesn't fit `{}`",
+            flag,
+            path,
+            type_text(ty)
+        )
+    };
+
+    let (segment, args) = match ty {
+        syn::Type::Group(group) => return explicit_expr(value, &group.elem, path, flag),
+        syn::Type::Paren(paren) => return explicit_expr(value, &paren.elem, path, flag),
+
+        syn::Type::Tuple(tuple) => {
+            let JsonValue::Array(items) = value else {
+                return Err(mismatch());
+            };
+
+            if items.len() != tuple.elems.len() {
+                return Err(mismatch());
+            }
+
+            let items = items
+                .iter()
+                .zip(&tuple.elems)
+                .map(|(item, ty)| explicit_expr(item, ty, &element_path(path), flag))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            return Ok(quote!((#(#items,)*)));
+        }
+
+        // The length is checked by the compiler
+        syn::Type::Array(array) => {
+            let JsonValue::Array(items) = value else {
+                return Err(mismatch());
+            };
+
+            let items = items
+                .iter()
+                .map(|item| explicit_expr(item, &array.elem, &element_path(path), flag))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            return Ok(quote!([#(#items),*]));
+        }
+
+        syn::Type::Path(type_path) if type_path.qself.is_none() => {
+            let Some(segment) = type_path.path.segments.last() else {
+                return Err(mismatch());
+            };
+
+            let args = match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                _ => Vec::new(),
+            };
+
+            (segment.ident.to_string(), args)
+        }
+        syn::Type::Path(_) => return Ok(deserialize_expr(value, ty, path)),
+
+        _ => return Err(not_owned(ty, path, flag)),
+    };
+
+    // The annotation is already part of the type
+    let value = match value {
+        JsonValue::Annotated(value, _) => value.as_ref(),
+        value => value,
+    };
+
+    let expr = match (segment.as_str(), args.as_slice(), value) {
+        (int_type, [], JsonValue::Number(number)) if integer_range(int_type).is_some() => {
+            let fits = match number {
+                JsonNumber::Int(i, _) => {
+                    integer_range(int_type).is_some_and(|(min, max)| (min..=max).contains(i))
+                }
+                JsonNumber::Float(..) => false,
+            };
+
+            if !fits {
+                return Err(mismatch());
+            }
+
+            number_literal(number, int_type)
+        }
+        (float_type @ ("f32" | "f64"), [], JsonValue::Number(number)) => {
+            if !is_finite_as(number, float_type) {
+                return Err(mismatch());
+            }
+
+            number_literal(number, float_type)
+        }
+        ("bool", [], JsonValue::Boolean(b)) => quote!(#b),
+        ("char", [], JsonValue::Str(s)) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => quote!(#c),
+                _ => return Err(mismatch()),
+            }
+        }
+        ("String", [], JsonValue::Str(s)) => quote!(::std::string::String::from(#s)),
+
+        ("Option", [_], JsonValue::Null) => quote!(::std::option::Option::None),
+        ("Option", [inner], value) => {
+            let expr = explicit_expr(value, inner, path, flag)?;
+            quote!(::std::option::Option::Some(#expr))
+        }
+        ("Box", [inner], value) => {
+            let expr = explicit_expr(value, inner, path, flag)?;
+            quote!(::std::boxed::Box::new(#expr))
+        }
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner], JsonValue::Array(items)) => {
+            let items = items
+                .iter()
+                .map(|item| explicit_expr(item, inner, &element_path(path), flag))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if segment == "Vec" {
+                quote!(::std::vec![#(#items),*])
+            } else {
+                quote!(::std::iter::FromIterator::from_iter([#(#items),*]))
+            }
+        }
+        ("HashMap" | "BTreeMap", [key_type, value_type], JsonValue::Object(entries)) => {
+            let entries = entries
+                .iter()
+                .map(|(key, value)| {
+                    let value_path = field_path(path, key);
+                    let key = explicit_expr(&JsonValue::Str(key.clone()), key_type, path, flag)?;
+                    let value = explicit_expr(value, value_type, &value_path, flag)?;
+                    Ok(quote!((#key, #value)))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+
+            quote!(::std::iter::FromIterator::from_iter([#(#entries),*]))
+        }
+        ("Value", [], value) => json_value_expr(value),
+
+        (name, _, _) if has_literal_form(name) => return Err(mismatch()),
+        _ => deserialize_expr(value, ty, path),
+    };
+
+    Ok(expr)
+}
+
+/// Whether values of a type can be written as literals, by the name of the type
+fn has_literal_form(name: &str) -> bool {
+    integer_range(name).is_some()
+        || matches!(
+            name,
+            "f32"
+                | "f64"
+                | "bool"
+                | "char"
+                | "String"
+                | "Option"
+                | "Box"
+                | "Vec"
+                | "VecDeque"
+                | "HashSet"
+                | "BTreeSet"
+                | "HashMap"
+                | "BTreeMap"
+                | "Value"
+        )
+}
+
+/// Deserializes a value from a `serde_json::Value`, for types that can't be written as literals
+fn deserialize_expr(value: &JsonValue, ty: &syn::Type, path: &str) -> proc_macro2::TokenStream {
+    let json = json_value_expr(value);
+    let message = format!(
+        "the sample value at `{}` doesn't fit `{}`",
+        path,
+        type_text(ty)
+    );
+
+    quote!(::serde_json::from_value::<#ty>(#json).expect(#message))
+}
+
+/// The error for a type that can't be built from a JSON value, like a reference
+fn not_owned(ty: &syn::Type, path: &str, flag: &str) -> String {
+    format!(
+        "{}: `{}` at `{}` can't be built from the sample, annotate the value with an owned type instead",
+        flag,
+        type_text(ty),
+        path
+    )
+}
+
+/// Writes a type for error messages, `std::net::Ipv4Addr` rather than `std :: net :: Ipv4Addr`
+fn type_text(ty: &syn::Type) -> String {
+    let text = ty.to_token_stream().to_string();
+    let chars = text.chars().collect::<Vec<_>>();
+    let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+
+    // Spaces are only needed between words, `dyn Error`
+    chars
+        .iter()
+        .enumerate()
+        .filter(|(i, c)| {
+            **c != ' '
+                || (is_word(i.checked_sub(1).and_then(|i| chars.get(i)))
+                    && is_word(chars.get(i + 1)))
+        })
+        .map(|(_, c)| c)
+        .collect()
+}
+
+/// Writes a number as a literal of the given type, `-3i32`, `0.5f64`
+fn number_literal(number: &JsonNumber, number_type: &str) -> proc_macro2::TokenStream {
+    // Negative literals are written as a negated positive one
+    let (negative, literal) = match number {
+        // `-0` stays negative as a float
+        JsonNumber::Int(i, text) if number_type == "f32" || number_type == "f64" => (
+            text.starts_with('-'),
+            float_literal(i.unsigned_abs() as f64, number_type),
+        ),
+        JsonNumber::Int(i, _) => {
+            let literal = syn::LitInt::new(
+                &format!("{}{}", i.unsigned_abs(), number_type),
+                proc_macro2::Span::call_site(),
+            );
+            (*i < 0, literal.into_token_stream())
+        }
+        JsonNumber::Float(f, _) => (f.is_sign_negative(), float_literal(f.abs(), number_type)),
+    };
+
+    if negative {
+        quote!(-#literal)
+    } else {
+        literal
+    }
+}
+
+/// Whether a number stays finite as a float type, `1e300` doesn't fit an `f32`
+fn is_finite_as(number: &JsonNumber, float_type: &str) -> bool {
+    let value = match number {
+        JsonNumber::Int(i, _) => *i as f64,
+        JsonNumber::Float(f, _) => *f,
+    };
+
+    if float_type == "f32" {
+        (value as f32).is_finite()
+    } else {
+        value.is_finite()
+    }
+}
+
+/// Writes a positive float literal, numbers too big for the type are infinite
+fn float_literal(value: f64, float_type: &str) -> proc_macro2::TokenStream {
+    let float_type = format_ident!("{}", float_type);
+
+    if float_type == "f32" && (value as f32).is_finite() {
+        proc_macro2::Literal::f32_suffixed(value as f32).into_token_stream()
+    } else if float_type == "f64" && value.is_finite() {
+        proc_macro2::Literal::f64_suffixed(value).into_token_stream()
+    } else {
+        quote!(::std::#float_type::INFINITY)
+    }
+}
+
+/// Builds a `serde_json::Value`
+fn json_value_expr(value: &JsonValue) -> proc_macro2::TokenStream {
+    match value {
+        JsonValue::Null => quote!(::serde_json::Value::Null),
+        JsonValue::Boolean(b) => quote!(::serde_json::Value::Bool(#b)),
+        JsonValue::Str(s) => quote!(::serde_json::Value::String(::std::string::String::from(#s))),
+        JsonValue::Number(number) => {
+            let literal = match number {
+                JsonNumber::Int(i, _) if i64::try_from(*i).is_ok() => number_literal(number, "i64"),
+                JsonNumber::Int(i, _) if u64::try_from(*i).is_ok() => number_literal(number, "u64"),
+                _ => number_literal(number, "f64"),
+            };
+
+            quote!(::serde_json::Value::from(#literal))
+        }
+        JsonValue::Array(items) => {
+            let items = items.iter().map(json_value_expr);
+            quote!(::serde_json::Value::Array(::std::vec![#(#items),*]))
+        }
+        JsonValue::Object(entries) => {
+            let entries = entries.iter().map(|(key, value)| {
+                let value = json_value_expr(value);
+                quote!((::std::string::String::from(#key), #value))
+            });
+
+            quote!(::serde_json::Value::Object(::std::iter::FromIterator::from_iter([#(#entries),*])))
+        }
+        JsonValue::Annotated(value, _) => json_value_expr(value),
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::{HashMap, HashSet};
+
+use quote::{quote, ToTokens};
+use serde_json::{json, Map, Value};
+use syn::{ext::IdentExt, Ident};
+
+use crate::generator::{
+    element_path, field_path, map_value_path, serialized_name, variant_path, GeneratedStruct,
+};
+use crate::naming;
+use crate::parser::{integer_range, JsonMacroFlags, JsonStruct};
+use crate::shape::{Shape, TaggedVariant};
+
+/// The dialect of the generated schemas
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Generates the JSON Schema of the main struct, as `JSON_SCHEMA` text and `fn json_schema()`.
+///
+/// Nested structs and enums are in `$defs`, properties have the names the fields are serialized with,
+/// and the samples are the schema's `examples`.
+///
+/// `Example`
+///
+/// ```rust,ignore
+/// impl User {
+///     pub const JSON_SCHEMA: &'static str = "{\"$schema\": ..., \"title\": \"User\", ...}";
+///
+///     pub fn json_schema() -> ::serde_json::Value {
+///         ::serde_json::from_str(Self::JSON_SCHEMA).expect(..)
+///     }
+/// }
+/// ```
+///
+/// # Parameters
+/// - `struct_name`: The name of the main struct
+/// - `samples`: Every sample, in order
+/// - `types`: The names of the generated types, by the path of the values they hold
+/// - `structs`: Every generated struct
+/// - `flags`: The macro flags
+///
+/// # Errors
+/// If a sample can't be written as JSON
+pub fn schema_impl(
+    struct_name: &Ident,
+    samples: &[&JsonStruct],
+    types: &HashMap<String, Ident>,
+    structs: &[GeneratedStruct],
+    flags: &JsonMacroFlags,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let error = |message: String| syn::Error::new(struct_name.span(), message);
+
+    let mut writer = SchemaWriter::new(struct_name, types, structs, flags);
+    let root = writer.struct_schema(struct_name).map_err(error)?;
+
+    let root_shape = structs
+        .iter()
+        .find(|generated| generated.name == *struct_name)
+        .map(|generated| Shape::Object(generated.fields.clone()))
+        .unwrap_or(Shape::Unknown);
+
+    // The samples are written the same way as with `@store_json`, with the keys renamed like the fields
+    let examples = samples
+        .iter()
+        .map(|sample| {
+            let text = sample.to_json_text()?;
+            let value = serde_json::from_str::<Value>(&text).map_err(|err| err.to_string())?;
+            Ok(writer.example(value, &root_shape, ""))
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(error)?;
+
+    let mut schema = Map::new();
+    schema.insert("$schema".into(), json!(DRAFT));
+    schema.insert("title".into(), json!(struct_name.to_string()));
+    schema.extend(root);
+
+    if !examples.is_empty() {
+        schema.insert("examples".into(), Value::Array(examples));
+    }
+
+    if !writer.defs.is_empty() {
+        schema.insert("$defs".into(), Value::Object(writer.defs));
+    }
+
+    let schema_text =
+        serde_json::to_string_pretty(&schema).map_err(|err| error(err.to_string()))?;
+
+    Ok(quote! {
+        impl #struct_name {
+            /// The JSON Schema (draft 2020-12) of this type, as text
+            pub const JSON_SCHEMA: &'static str = #schema_text;
+
+            /// The JSON Schema (draft 2020-12) of this type
+            pub fn json_schema() -> ::serde_json::Value {
+                ::serde_json::from_str(Self::JSON_SCHEMA)
+                    .expect("Couldn't convert the schema into valid json")
+            }
+        }
+    })
+}
+
+/// Builds the schemas of the generated types, following the shapes they were generated from
+struct SchemaWriter<'a> {
+    /// The main struct, the root of the schema
+    root: &'a Ident,
+    types: &'a HashMap<String, Ident>,
+    structs: HashMap<&'a Ident, &'a GeneratedStruct>,
+    flags: &'a JsonMacroFlags,
+    /// The schemas of the nested types, by name, in the order they're first referenced
+    defs: Map<String, Value>,
+}
+
+impl<'a> SchemaWriter<'a> {
+    fn new(
+        root: &'a Ident,
+        types: &'a HashMap<String, Ident>,
+        structs: &'a [GeneratedStruct],
+        flags: &'a JsonMacroFlags,
+    ) -> Self {
+        let structs = structs
+            .iter()
+            .map(|generated| (&generated.name, generated))
+            .collect();
+
+        SchemaWriter {
+            root,
+            types,
+            structs,
+            flags,
+            defs: Map::new(),
+        }
+    }
+
+    /// The name of the type generated for the values at a path
+    fn type_at(&self, path: &str) -> Result<&'a Ident, String> {
+        self.types
+            .get(path)
+            .ok_or_else(|| format!("@json_schema: no type was generated for `{}`", path))
+    }
+
+    /// Refers to a nested type, adding its schema to `$defs` the first time
+    fn def_ref(
+        &mut self,
+        name: &Ident,
+        schema: impl FnOnce(&mut Self) -> Result<Map<String, Value>, String>,
+    ) -> Result<Value, String> {
+        let key = name.to_string();
+
+        // The entry is taken first, so a type's definition comes before the ones nested in it
+        if !self.defs.contains_key(&key) {
+            self.defs.insert(key.clone(), Value::Null);
+            let schema = schema(self)?;
+            self.defs.insert(key.clone(), Value::Object(schema));
+        }
+
+        Ok(json!({ "$ref": format!("#/$defs/{}", key) }))
+    }
+
+    /// The schema of a generated struct, with a property per field
+    ///
+    /// A struct shared by several objects is generated once,
+    /// so the types nested in it are looked up from where it was generated.
+    fn struct_schema(&mut self, struct_name: &Ident) -> Result<Map<String, Value>, String> {
+        let generated = *self.structs.get(struct_name).ok_or_else(|| {
+            format!(
+                "@json_schema: the struct `{}` wasn't generated",
+                struct_name
+            )
+        })?;
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        for (field, name) in generated.fields.iter().zip(self.property_names(generated)) {
+            let path = field_path(&generated.path, &field.key);
+
+            // Optional fields also accept `null`
+            let schema = match &field.shape {
+                Shape::Null => json!({}),
+                Shape::Optional(inner) => nullable(self.shape_schema(inner, &path)?),
+                shape if field.missing => nullable(self.shape_schema(shape, &path)?),
+                shape => {
+                    required.push(json!(name));
+                    self.shape_schema(shape, &path)?
+                }
+            };
+
+            properties.insert(name, schema);
+        }
+
+        let mut schema = Map::new();
+        schema.insert("type".into(), json!("object"));
+        schema.insert("properties".into(), Value::Object(properties));
+
+        // With `@default_from_sample` missing fields take their default value
+        if !required.is_empty() && !self.flags.default_from_sample {
+            schema.insert("required".into(), Value::Array(required));
+        }
+
+        Ok(schema)
+    }
+
+    /// The names of the properties of a struct, the names its fields are serialized with
+    fn property_names(&self, generated: &GeneratedStruct) -> Vec<String> {
+        // Field names are given in the same order as when the struct was generated
+        let mut taken = HashSet::new();
+
+        generated
+            .fields
+            .iter()
+            .map(|field| {
+                let field_name = naming::field_ident(&field.key, &mut taken);
+                serialized_name(&field_name.unraw().to_string(), &field.key, self.flags)
+            })
+            .collect()
+    }
+
+    /// Renames the keys of a sample to the property names, following the shapes of the values
+    fn example(&self, value: Value, shape: &Shape, path: &str) -> Value {
+        match (shape, value) {
+            (Shape::Optional(inner) | Shape::Named { shape: inner, .. }, value) => {
+                self.example(value, inner, path)
+            }
+            (Shape::Array(inner), Value::Array(items)) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.example(item, inner, &element_path(path)))
+                    .collect(),
+            ),
+            (Shape::Object(_) | Shape::Recursive(_), Value::Object(entries)) => {
+                self.example_object(entries, path)
+            }
+            (Shape::Map(inner), Value::Object(entries)) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, self.example(value, inner, &map_value_path(path))))
+                    .collect(),
+            ),
+            (Shape::Union(members), value) => {
+                let member = members.iter().find(|member| match &value {
+                    Value::Object(_) => {
+                        matches!(member.resolved(), Shape::Object(_) | Shape::Map(_))
+                    }
+                    Value::Array(_) => matches!(member.resolved(), Shape::Array(_)),
+                    _ => false,
+                });
+
+                match member {
+                    Some(member) => {
+                        self.example(value, member, &variant_path(path, union_variant(member)))
+                    }
+                    None => value,
+                }
+            }
+            // The tag isn't a field, it's kept as it is
+            (Shape::Tagged { tag, .. }, Value::Object(entries)) => {
+                match entries.get(tag).and_then(Value::as_str) {
+                    Some(tag_value) => {
+                        let variant_path = variant_path(path, tag_value);
+                        self.example_object(entries, &variant_path)
+                    }
+                    None => Value::Object(entries),
+                }
+            }
+            (_, value) => value,
+        }
+    }
+
+    /// Renames the keys of an object of the struct generated at a path
+    fn example_object(&self, entries: Map<String, Value>, path: &str) -> Value {
+        let Some(generated) = self.types.get(path).and_then(|name| self.structs.get(name)) else {
+            return Value::Object(entries);
+        };
+
+        let names = self.property_names(generated);
+
+        let entries = entries
+            .into_iter()
+            .map(
+                |(key, value)| match generated.fields.iter().position(|field| field.key == key) {
+                    Some(index) => {
+                        let field = &generated.fields[index];
+                        let field_path = field_path(&generated.path, &key);
+                        let value = self.example(value, &field.shape, &field_path);
+
+                        (names[index].clone(), value)
+                    }
+                    None => (key, value),
+                },
+            )
+            .collect();
+
+        Value::Object(entries)
+    }
+
+    /// The schema of the values of a shape
+    fn shape_schema(&mut self, shape: &Shape, path: &str) -> Result<Value, String> {
+        let schema = match shape {
+            Shape::Str => json!({ "type": "string" }),
+            Shape::Int { .. } => json!({ "type": "integer" }),
+            Shape::Float => json!({ "type": "number" }),
+            Shape::Bool => json!({ "type": "boolean" }),
+
+            // Empty arrays don't say anything about their elements
+            Shape::Array(elem) if matches!(elem.as_ref(), Shape::Unknown) => {
+                json!({ "type": "array" })
+            }
+            Shape::Array(elem) => {
+                let items = self.shape_schema(elem, &element_path(path))?;
+                json!({ "type": "array", "items": items })
+            }
+
+            Shape::Object(_) => {
+                let name = self.type_at(path)?;
+                self.def_ref(name, |writer| writer.struct_schema(name))?
+            }
+
+            Shape::Optional(inner) => nullable(self.shape_schema(inner, path)?),
+
+            // Every `$ref` to a definition has the type generated for it, so they share its `$defs` entry
+            Shape::Named { shape, .. } => self.shape_schema(shape, path)?,
+
+            Shape::Map(value) => {
+                let values = self.shape_schema(value, &map_value_path(path))?;
+                json!({ "type": "object", "additionalProperties": values })
+            }
+
+            Shape::Enum(values) => {
+                let name = self.type_at(path)?;
+                self.def_ref(name, |_| {
+                    Ok(Map::from_iter([
+                        ("type".into(), json!("string")),
+                        ("enum".into(), json!(values)),
+                    ]))
+                })?
+            }
+
+            // Untagged enums are any of their variants
+            Shape::Union(members) => {
+                let name = self.type_at(path)?;
+                self.def_ref(name, |writer| {
+                    let members = members
+                        .iter()
+                        .map(|member| {
+                            let member_path = variant_path(path, union_variant(member));
+                            writer.shape_schema(member, &member_path)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    Ok(Map::from_iter([("anyOf".into(), Value::Array(members))]))
+                })?
+            }
+
+            // Tagged enums are one of their variant structs, with the tag set to the variant's value
+            Shape::Tagged { tag, variants } => {
+                let name = self.type_at(path)?;
+                self.def_ref(name, |writer| {
+                    let variants = variants
+                        .iter()
+                        .map(|variant| writer.tagged_variant_schema(tag, variant, path))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    Ok(Map::from_iter([("oneOf".into(), Value::Array(variants))]))
+                })?
+            }
+
+            Shape::Explicit(ty) => explicit_schema(ty),
+
+            // A struct that holds itself refers to its own definition, the main struct is the whole document
+            Shape::Recursive(_) => match self.types.get(path) {
+                Some(name) if name == self.root => json!({ "$ref": "#" }),
+                Some(name) => self.def_ref(name, |writer| writer.struct_schema(name))?,
+                // A definition merged with other types has no struct to refer to
+                None => json!({}),
+            },
+
+            // `null`, empty arrays, and conflicting types with `@lenient` are `serde_json::Value`
+            Shape::Null | Shape::Unknown | Shape::Any => json!({}),
+        };
+
+        Ok(schema)
+    }
+
+    /// The schema of a variant of a tagged enum, the tag isn't a field of the variant's struct
+    fn tagged_variant_schema(
+        &mut self,
+        tag: &str,
+        variant: &TaggedVariant,
+        path: &str,
+    ) -> Result<Value, String> {
+        let name = self.type_at(&variant_path(path, &variant.tag_value))?;
+        let variant_ref = self.def_ref(name, |writer| writer.struct_schema(name))?;
+
+        Ok(json!({
+            "$ref": variant_ref["$ref"],
+            "properties": { tag: { "const": variant.tag_value } },
+            "required": [tag],
+        }))
+    }
+}
+
+/// The variant of an untagged enum holding a member, the same ones the generator uses
+fn union_variant(member: &Shape) -> &'static str {
+    match member.resolved() {
+        Shape::Str | Shape::Enum(_) => "Str",
+        Shape::Int { .. } | Shape::Float => "Num",
+        Shape::Bool => "Bool",
+        Shape::Object(_) => "Obj",
+        Shape::Array(_) => "Arr",
+        Shape::Map(_) => "Map",
+        _ => "Other",
+    }
+}
+
+/// Also accepts `null`, a schema accepting anything is kept as is
+fn nullable(schema: Value) -> Value {
+    if schema == json!({}) {
+        schema
+    } else {
+        json!({ "anyOf": [schema, { "type": "null" }] })
+    }
+}
+
+/// The schema of a type given in the input, primitive types are known and anything else is accepted
+fn explicit_schema(ty: &syn::Type) -> Value {
+    let type_name = ty.to_token_stream().to_string();
+
+    match type_name.as_str() {
+        _ if integer_range(&type_name).is_some() => json!({ "type": "integer" }),
+        "f32" | "f64" => json!({ "type": "number" }),
+        "bool" => json!({ "type": "boolean" }),
+        "String" => json!({ "type": "string" }),
+        _ => json!({}),
+    }
+}
@@ -0,0 +1,796 @@
+use crate::generator::integer_type;
+use crate::naming;
+use crate::parser::{JsonMacroFlags, JsonNumber, JsonStruct, JsonValue};
+
+/// The inferred type of a JSON value.
+///
+/// Every value seen at the same place (all the elements of an array, the same key across objects)
+/// is merged into a single shape, so the generated type fits all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Nothing was seen, like the elements of an empty array
+    Unknown,
+    /// Only `null` was seen
+    Null,
+    Bool,
+    /// Integers, with the smallest and largest value seen
+    Int {
+        min: i128,
+        max: i128,
+    },
+    Float,
+    Str,
+    Array(Box<Shape>),
+    Object(Vec<FieldShape>),
+    /// A value that can also be `null`
+    Optional(Box<Shape>),
+    /// A type given in the input, used verbatim
+    Explicit(Box<syn::Type>),
+    /// Conflicting types, kept as a `serde_json::Value` (only with `@lenient`)
+    Any,
+    /// Conflicting types, kept as an untagged enum with one variant per kind (only with `@untagged`)
+    Union(Vec<Shape>),
+    /// Objects told apart by a tag field, one variant per tag value (only with `@tagged(...)`)
+    Tagged {
+        tag: String,
+        variants: Vec<TaggedVariant>,
+    },
+    /// Objects with any keys, from a schema's `additionalProperties`
+    Map(Box<Shape>),
+    /// One of a fixed set of strings, from a schema's `enum`
+    Enum(Vec<String>),
+    /// A type a schema refers to with `$ref`, generated once with the name of its definition
+    /// and reused everywhere it's referred to
+    Named {
+        /// The `$ref`, `#/$defs/Address`
+        reference: String,
+        /// The name of the type, `Address`
+        name: String,
+        shape: Box<Shape>,
+    },
+    /// A schema object that refers to itself, by the `$ref` of its [`Shape::Named`] (`#` is the main struct)
+    Recursive(String),
+}
+
+/// A variant of a tagged shape, its fields don't include the tag
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedVariant {
+    pub tag_value: String,
+    /// The name of the enum variant, unique among the variants of the shape
+    pub name: String,
+    pub fields: Vec<FieldShape>,
+}
+
+impl TaggedVariant {
+    /// A variant named after its tag value.
+    ///
+    /// Tag values can give the same name, `page-view` and `page_view`, so later ones are numbered (`PageView2`).
+    ///
+    /// # Parameters
+    /// - `tag_value`: The value of the tag field
+    /// - `fields`: The fields of the variant, without the tag
+    /// - `others`: The variants already in the shape
+    pub fn new(tag_value: String, fields: Vec<FieldShape>, others: &[TaggedVariant]) -> Self {
+        let name = naming::type_name(&tag_value, false);
+        let name = (1..)
+            .map(|n| match n {
+                1 => name.clone(),
+                n => format!("{}{}", name, n),
+            })
+            .find(|name| others.iter().all(|other| other.name != *name))
+            .unwrap_or_default();
+
+        TaggedVariant {
+            tag_value,
+            name,
+            fields,
+        }
+    }
+}
+
+/// A field of an object shape
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldShape {
+    pub key: String,
+    pub shape: Shape,
+    /// The field was missing from some of the objects
+    pub missing: bool,
+}
+
+impl Shape {
+    /// A short description used in error messages
+    fn describe(&self) -> &'static str {
+        match self {
+            Shape::Unknown => "unknown",
+            Shape::Null => "null",
+            Shape::Bool => "bool",
+            Shape::Int { .. } => "integer",
+            Shape::Float => "float",
+            Shape::Str => "string",
+            Shape::Array(_) => "array",
+            Shape::Object(_) => "object",
+            Shape::Optional(inner) => inner.describe(),
+            Shape::Explicit(_) => "annotated type",
+            Shape::Any => "any",
+            Shape::Union(_) => "union",
+            Shape::Tagged { .. } => "tagged object",
+            Shape::Map(_) => "map",
+            Shape::Enum(_) => "string",
+            Shape::Named { shape, .. } => shape.describe(),
+            Shape::Recursive(_) => "object",
+        }
+    }
+
+    /// The shape a [`Shape::Named`] stands for, other shapes are themselves
+    pub fn resolved(&self) -> &Shape {
+        match self {
+            Shape::Named { shape, .. } => shape,
+            shape => shape,
+        }
+    }
+
+    /// Whether two shapes hold the same kind of JSON value, integers and floats are both numbers
+    fn same_kind(&self, other: &Shape) -> bool {
+        matches!(
+            (self.resolved(), other.resolved()),
+            (Shape::Bool, Shape::Bool)
+                | (Shape::Str | Shape::Enum(_), Shape::Str | Shape::Enum(_))
+                | (
+                    Shape::Int { .. } | Shape::Float,
+                    Shape::Int { .. } | Shape::Float
+                )
+                | (Shape::Array(_), Shape::Array(_))
+                | (Shape::Object(_), Shape::Object(_))
+                | (Shape::Map(_), Shape::Map(_))
+                | (Shape::Recursive(_), Shape::Recursive(_))
+        )
+    }
+}
+
+/// Infers the shape of a single JSON value.
+///
+/// # Parameters
+/// - `value`: The JSON value
+/// - `path`: Where the value is, used in error messages (`employees[].id`)
+/// - `flags`: The macro flags
+///
+/// # Returns
+/// The shape, or an error message if the elements of an array conflict
+pub fn infer(value: &JsonValue, path: &str, flags: &JsonMacroFlags) -> Result<Shape, String> {
+    let shape = match value {
+        JsonValue::Str(_) => Shape::Str,
+        JsonValue::Number(JsonNumber::Int(i, _)) => int(*i, *i, path, flags)?,
+        JsonValue::Number(JsonNumber::Float(..)) => Shape::Float,
+        JsonValue::Boolean(_) => Shape::Bool,
+        JsonValue::Null => Shape::Null,
+        JsonValue::Annotated(value, ty) => match value.as_ref() {
+            // `null as String` is an `Option<String>`, unless the annotation already is one
+            JsonValue::Null if !is_option_type(ty) => {
+                Shape::Optional(Box::new(Shape::Explicit(ty.clone())))
+            }
+            _ => Shape::Explicit(ty.clone()),
+        },
+        JsonValue::Array(arr) => {
+            let elem_path = format!("{}[]", path);
+
+            // Objects sharing the `@tagged(...)` field are grouped by its value
+            if let Some(tag) = &flags.tagged {
+                if let Some(tagged) = infer_tagged(arr, tag, &elem_path, flags)? {
+                    return Ok(Shape::Array(Box::new(tagged)));
+                }
+            }
+
+            // Every element feeds the merge, not just the first one
+            let mut elem_shape = Shape::Unknown;
+            for elem in arr {
+                let shape = infer(elem, &elem_path, flags)?;
+                elem_shape = merge(elem_shape, shape, &elem_path, flags)?;
+            }
+
+            Shape::Array(Box::new(elem_shape))
+        }
+        JsonValue::Object(obj) => Shape::Object(infer_fields(obj, path, flags)?),
+    };
+
+    Ok(shape)
+}
+
+/// Infers the field shapes of one or more samples of the same object.
+///
+/// Fields missing from some of the samples are marked as missing.
+pub fn infer_samples<'a>(
+    samples: impl IntoIterator<Item = &'a JsonStruct>,
+    flags: &JsonMacroFlags,
+) -> Result<Vec<FieldShape>, String> {
+    let mut fields: Option<Vec<FieldShape>> = None;
+
+    for sample in samples {
+        let sample_fields = infer_fields(&sample.entries, "", flags)?;
+
+        fields = Some(match fields {
+            Some(fields) => merge_fields(fields, sample_fields, "", flags)?,
+            None => sample_fields,
+        });
+    }
+
+    Ok(fields.unwrap_or_default())
+}
+
+/// Groups the elements of an array by the value of their tag field.
+///
+/// # Returns
+/// The tagged shape, or `None` if some element isn't an object with a string tag
+fn infer_tagged(
+    arr: &[JsonValue],
+    tag: &str,
+    path: &str,
+    flags: &JsonMacroFlags,
+) -> Result<Option<Shape>, String> {
+    let mut variants: Vec<TaggedVariant> = Vec::new();
+
+    for elem in arr {
+        let JsonValue::Object(obj) = elem else {
+            return Ok(None);
+        };
+
+        let Some(tag_value) = obj.iter().find_map(|(key, value)| match value {
+            JsonValue::Str(tag_value) if key == tag => Some(tag_value.clone()),
+            _ => None,
+        }) else {
+            return Ok(None);
+        };
+
+        let variant_path = format!("{}[{}={}]", path, tag, tag_value);
+
+        let fields = obj
+            .iter()
+            .filter(|(key, _)| key != tag)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let fields = infer_fields(&fields, &variant_path, flags)?;
+        let variant = TaggedVariant::new(tag_value, fields, &variants);
+
+        variants = merge_into_tagged(variants, variant, &variant_path, flags)?;
+    }
+
+    if variants.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(Shape::Tagged {
+        tag: tag.to_string(),
+        variants,
+    }))
+}
+
+/// Adds a variant to a tagged shape, merging it with the variant of the same tag value if there is one.
+fn merge_into_tagged(
+    mut variants: Vec<TaggedVariant>,
+    variant: TaggedVariant,
+    path: &str,
+    flags: &JsonMacroFlags,
+) -> Result<Vec<TaggedVariant>, String> {
+    match variants
+        .iter_mut()
+        .find(|other| other.tag_value == variant.tag_value)
+    {
+        Some(other) => {
+            let fields = std::mem::take(&mut other.fields);
+            other.fields = merge_fields(fields, variant.fields, path, flags)?;
+        }
+        // Variants from another tagged shape are renamed, their name may be taken here
+        None => {
+            let variant = TaggedVariant::new(variant.tag_value, variant.fields, &variants);
+            variants.push(variant);
+        }
+    }
+
+    Ok(variants)
+}
+
+/// Infers the field shapes of a JSON object.
+pub fn infer_fields(
+    obj: &[(String, JsonValue)],
+    path: &str,
+    flags: &JsonMacroFlags,
+) -> Result<Vec<FieldShape>, String> {
+    obj.iter()
+        .map(|(key, value)| {
+            Ok(FieldShape {
+                key: key.clone(),
+                shape: infer(value, &join_path(path, key), flags)?,
+                missing: false,
+            })
+        })
+        .collect()
+}
+
+/// Merges two shapes into one that fits the values of both.
+///
+/// - `null` next to a type makes it `Option<T>`
+/// - an annotated type is kept over anything inferred
+/// - integers next to floats widen to `f64`
+/// - sets of strings join together, and widen to `String` next to any string
+/// - fields missing from one of two objects are marked as missing
+/// - anything else is a conflict, which becomes an untagged enum with `@untagged`,
+///   `serde_json::Value` with `@lenient`, or an error
+///
+/// # Parameters
+/// - `a`, `b`: The shapes to merge
+/// - `path`: Where the values are, used in error messages
+/// - `flags`: The macro flags
+///
+/// # Returns
+/// The merged shape, or an error message describing the conflict
+pub fn merge(a: Shape, b: Shape, path: &str, flags: &JsonMacroFlags) -> Result<Shape, String> {
+    let shape = match (a, b) {
+        (Shape::Unknown, other) | (other, Shape::Unknown) => other,
+        (Shape::Any, _) | (_, Shape::Any) => Shape::Any,
+
+        (Shape::Null, Shape::Null) => Shape::Null,
+        (Shape::Null, Shape::Optional(inner)) | (Shape::Optional(inner), Shape::Null) => {
+            Shape::Optional(inner)
+        }
+        (Shape::Null, other) | (other, Shape::Null) => Shape::Optional(Box::new(other)),
+        (Shape::Optional(a), Shape::Optional(b)) => {
+            Shape::Optional(Box::new(merge(*a, *b, path, flags)?))
+        }
+        (Shape::Optional(a), b) | (b, Shape::Optional(a)) => {
+            match merge(*a, b, path, flags)? {
+                // Conflicts under `@lenient` are already nullable
+                Shape::Any => Shape::Any,
+                merged => Shape::Optional(Box::new(merged)),
+            }
+        }
+
+        // The annotated type wins over whatever was inferred
+        (Shape::Explicit(ty), _) | (_, Shape::Explicit(ty)) => Shape::Explicit(ty),
+
+        // A schema definition merged with anything but itself is no longer that type, so it loses its name
+        (
+            Shape::Named {
+                reference: a_reference,
+                name,
+                shape,
+            },
+            Shape::Named {
+                reference: b_reference,
+                ..
+            },
+        ) if a_reference == b_reference => Shape::Named {
+            reference: a_reference,
+            name,
+            shape,
+        },
+        (Shape::Named { shape, .. }, other) | (other, Shape::Named { shape, .. }) => {
+            merge(*shape, other, path, flags)?
+        }
+
+        (Shape::Bool, Shape::Bool) => Shape::Bool,
+        (Shape::Str, Shape::Str) => Shape::Str,
+        (Shape::Float, Shape::Float) => Shape::Float,
+        (
+            Shape::Int {
+                min: a_min,
+                max: a_max,
+            },
+            Shape::Int {
+                min: b_min,
+                max: b_max,
+            },
+        ) => int(a_min.min(b_min), a_max.max(b_max), path, flags)?,
+        (Shape::Int { .. }, Shape::Float) | (Shape::Float, Shape::Int { .. }) => Shape::Float,
+
+        (Shape::Array(a), Shape::Array(b)) => {
+            Shape::Array(Box::new(merge(*a, *b, &format!("{}[]", path), flags)?))
+        }
+        (Shape::Object(a), Shape::Object(b)) => Shape::Object(merge_fields(a, b, path, flags)?),
+        (Shape::Map(a), Shape::Map(b)) => Shape::Map(Box::new(merge(*a, *b, path, flags)?)),
+        (Shape::Recursive(a), Shape::Recursive(b)) if a == b => Shape::Recursive(a),
+
+        // Every value of both sets, any string when one of them isn't a set
+        (Shape::Enum(mut a), Shape::Enum(b)) => {
+            for value in b {
+                if !a.contains(&value) {
+                    a.push(value);
+                }
+            }
+            Shape::Enum(a)
+        }
+        (Shape::Enum(_), Shape::Str) | (Shape::Str, Shape::Enum(_)) => Shape::Str,
+
+        (
+            Shape::Tagged {
+                tag: a_tag,
+                variants: a,
+            },
+            Shape::Tagged {
+                tag: b_tag,
+                variants: b,
+            },
+        ) if a_tag == b_tag => {
+            let mut variants = a;
+            for variant in b {
+                variants = merge_into_tagged(variants, variant, path, flags)?;
+            }
+            Shape::Tagged {
+                tag: a_tag,
+                variants,
+            }
+        }
+
+        // Each kind of value gets one variant, values of a kind already seen merge into it
+        (Shape::Union(a), Shape::Union(b)) => {
+            let mut members = a;
+            for member in b {
+                members = merge_into_union(members, member, path, flags)?;
+            }
+            Shape::Union(members)
+        }
+        (Shape::Union(members), other) | (other, Shape::Union(members)) => {
+            Shape::Union(merge_into_union(members, other, path, flags)?)
+        }
+
+        (a, b) => {
+            if flags.untagged {
+                Shape::Union(vec![a, b])
+            } else if flags.lenient {
+                Shape::Any
+            } else {
+                return Err(format!(
+                    "Conflicting types for `{}`: {} and {}, use @untagged to generate an enum or @lenient to fall back to serde_json::Value",
+                    path,
+                    a.describe(),
+                    b.describe()
+                ));
+            }
+        }
+    };
+
+    Ok(shape)
+}
+
+/// Integers from `min` to `max`, as long as an integer type holds all of them.
+///
+/// # Errors
+/// When neither the `@int(...)` type, `i64` nor `u64` holds both, `-1` and `18446744073709551615`
+pub fn int(min: i128, max: i128, path: &str, flags: &JsonMacroFlags) -> Result<Shape, String> {
+    if integer_type(min, max, flags).is_none() {
+        return Err(format!(
+            "No integer type holds every value of `{}` ({} to {}), annotate the value with a type like `as f64` or `as i128`",
+            path, min, max
+        ));
+    }
+
+    Ok(Shape::Int { min, max })
+}
+
+/// Adds a shape to the members of a union, merging it with the member of the same kind if there is one.
+fn merge_into_union(
+    mut members: Vec<Shape>,
+    shape: Shape,
+    path: &str,
+    flags: &JsonMacroFlags,
+) -> Result<Vec<Shape>, String> {
+    match members.iter().position(|member| member.same_kind(&shape)) {
+        Some(index) => {
+            let member = members.remove(index);
+            members.insert(index, merge(member, shape, path, flags)?);
+        }
+        None => members.push(shape),
+    }
+
+    Ok(members)
+}
+
+/// Merges the fields of two object shapes, keeping the order they were first seen in.
+pub fn merge_fields(
+    a: Vec<FieldShape>,
+    b: Vec<FieldShape>,
+    path: &str,
+    flags: &JsonMacroFlags,
+) -> Result<Vec<FieldShape>, String> {
+    let mut b = b.into_iter().map(Some).collect::<Vec<_>>();
+    let mut fields = Vec::with_capacity(a.len());
+
+    for field in a {
+        let other = b
+            .iter_mut()
+            .find(|other| other.as_ref().is_some_and(|other| other.key == field.key))
+            .and_then(Option::take);
+
+        fields.push(match other {
+            Some(other) => FieldShape {
+                shape: merge(
+                    field.shape,
+                    other.shape,
+                    &join_path(path, &field.key),
+                    flags,
+                )?,
+                missing: field.missing || other.missing,
+                key: field.key,
+            },
+            None => FieldShape {
+                missing: true,
+                ..field
+            },
+        });
+    }
+
+    // Fields that only the second object has
+    fields.extend(b.into_iter().flatten().map(|field| FieldShape {
+        missing: true,
+        ..field
+    }));
+
+    Ok(fields)
+}
+
+/// Whether a type is written as `Option<...>`
+fn is_option_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Appends a key to a path used in error messages
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(min: i128, max: i128) -> Shape {
+        Shape::Int { min, max }
+    }
+
+    fn optional(shape: Shape) -> Shape {
+        Shape::Optional(Box::new(shape))
+    }
+
+    fn array(shape: Shape) -> Shape {
+        Shape::Array(Box::new(shape))
+    }
+
+    fn explicit(ty: &str) -> Shape {
+        Shape::Explicit(Box::new(syn::parse_str(ty).unwrap()))
+    }
+
+    fn named(reference: &str, shape: Shape) -> Shape {
+        Shape::Named {
+            reference: reference.to_string(),
+            name: "Tag".to_string(),
+            shape: Box::new(shape),
+        }
+    }
+
+    fn recursive(reference: &str) -> Shape {
+        Shape::Recursive(reference.to_string())
+    }
+
+    fn field(key: &str, shape: Shape, missing: bool) -> FieldShape {
+        FieldShape {
+            key: key.to_string(),
+            shape,
+            missing,
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn merges_compatible_shapes() {
+        let cases = [
+            (int(1, 1), Shape::Float, Shape::Float),
+            (Shape::Float, int(1, 1), Shape::Float),
+            (int(-3, 3), int(5, 10), int(-3, 10)),
+            (Shape::Null, Shape::Str, optional(Shape::Str)),
+            (Shape::Bool, Shape::Null, optional(Shape::Bool)),
+            (Shape::Null, Shape::Null, Shape::Null),
+            (optional(int(1, 1)), Shape::Float, optional(Shape::Float)),
+            (optional(Shape::Str), Shape::Null, optional(Shape::Str)),
+            (Shape::Unknown, Shape::Bool, Shape::Bool),
+            (array(int(1, 2)), array(Shape::Float), array(Shape::Float)),
+            (array(Shape::Unknown), array(Shape::Str), array(Shape::Str)),
+            (explicit("u8"), Shape::Str, explicit("u8")),
+            (Shape::Null, explicit("u8"), optional(explicit("u8"))),
+            (
+                Shape::Enum(strings(&["a"])),
+                Shape::Enum(strings(&["b", "a"])),
+                Shape::Enum(strings(&["a", "b"])),
+            ),
+            (Shape::Enum(strings(&["a"])), Shape::Str, Shape::Str),
+            (
+                recursive("#/$defs/Node"),
+                recursive("#/$defs/Node"),
+                recursive("#/$defs/Node"),
+            ),
+            (
+                Shape::Null,
+                recursive("#/$defs/Node"),
+                optional(recursive("#/$defs/Node")),
+            ),
+            (
+                named("#/$defs/Tag", Shape::Enum(strings(&["a"]))),
+                named("#/$defs/Tag", Shape::Enum(strings(&["a"]))),
+                named("#/$defs/Tag", Shape::Enum(strings(&["a"]))),
+            ),
+            (
+                named("#/$defs/Tag", Shape::Enum(strings(&["a"]))),
+                Shape::Str,
+                Shape::Str,
+            ),
+        ];
+
+        for (a, b, expected) in cases {
+            let merged = merge(a.clone(), b.clone(), "x", &JsonMacroFlags::default());
+            assert_eq!(merged, Ok(expected), "merging {:?} and {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn marks_fields_missing_from_one_object() {
+        let a = vec![
+            field("id", int(1, 1), false),
+            field("name", Shape::Str, false),
+        ];
+        let b = vec![
+            field("id", int(2, 2), false),
+            field("age", int(3, 3), false),
+        ];
+
+        let merged = merge_fields(a, b, "", &JsonMacroFlags::default());
+
+        assert_eq!(
+            merged,
+            Ok(vec![
+                field("id", int(1, 2), false),
+                field("name", Shape::Str, true),
+                field("age", int(3, 3), true),
+            ])
+        );
+    }
+
+    #[test]
+    fn keeps_fields_missing_once_missing() {
+        let a = vec![field("id", int(1, 1), true)];
+        let b = vec![field("id", int(2, 2), false)];
+
+        let merged = merge_fields(a, b, "", &JsonMacroFlags::default());
+
+        assert_eq!(merged, Ok(vec![field("id", int(1, 2), true)]));
+    }
+
+    #[test]
+    fn reports_conflicts_without_a_flag() {
+        let cases = [
+            (Shape::Str, int(1, 1), "string and integer"),
+            (Shape::Bool, array(Shape::Str), "bool and array"),
+            (optional(Shape::Str), Shape::Float, "string and float"),
+            (
+                recursive("#/$defs/Node"),
+                recursive("#"),
+                "object and object",
+            ),
+        ];
+
+        for (a, b, described) in cases {
+            let error = merge(a, b, "user.id", &JsonMacroFlags::default()).unwrap_err();
+
+            assert!(error.contains("`user.id`"), "{}", error);
+            assert!(error.contains(described), "{}", error);
+            assert!(error.contains("@untagged"), "{}", error);
+            assert!(error.contains("@lenient"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn falls_back_to_any_with_lenient() {
+        let flags = JsonMacroFlags {
+            lenient: true,
+            ..Default::default()
+        };
+
+        let cases = [
+            (Shape::Str, int(1, 1)),
+            (optional(Shape::Str), Shape::Bool),
+            (Shape::Any, Shape::Str),
+            (Shape::Object(Vec::new()), array(Shape::Unknown)),
+        ];
+
+        for (a, b) in cases {
+            assert_eq!(merge(a, b, "x", &flags), Ok(Shape::Any));
+        }
+    }
+
+    #[test]
+    fn builds_unions_with_untagged() {
+        let flags = JsonMacroFlags {
+            untagged: true,
+            ..Default::default()
+        };
+
+        let union = merge(Shape::Str, int(1, 1), "x", &flags).unwrap();
+        assert_eq!(union, Shape::Union(vec![Shape::Str, int(1, 1)]));
+
+        // Values of a kind already in the union merge into its member
+        let union = merge(union, Shape::Float, "x", &flags).unwrap();
+        assert_eq!(union, Shape::Union(vec![Shape::Str, Shape::Float]));
+
+        let union = merge(union, Shape::Bool, "x", &flags).unwrap();
+        assert_eq!(
+            union,
+            Shape::Union(vec![Shape::Str, Shape::Float, Shape::Bool])
+        );
+
+        // `null` makes the whole union optional
+        let union = merge(Shape::Null, union, "x", &flags).unwrap();
+        assert!(matches!(union, Shape::Optional(inner) if matches!(*inner, Shape::Union(_))));
+    }
+
+    #[test]
+    fn merges_tagged_variants_by_tag_value() {
+        let tagged = |tag_value: &str, fields| Shape::Tagged {
+            tag: "type".to_string(),
+            variants: vec![TaggedVariant::new(tag_value.to_string(), fields, &[])],
+        };
+
+        let merged = merge(
+            tagged("click", vec![field("x", int(1, 1), false)]),
+            tagged("click", vec![field("x", Shape::Float, false)]),
+            "x",
+            &JsonMacroFlags::default(),
+        );
+
+        assert_eq!(
+            merged,
+            Ok(tagged("click", vec![field("x", Shape::Float, false)]))
+        );
+    }
+
+    #[test]
+    fn numbers_variants_with_the_same_name() {
+        let flags = JsonMacroFlags::default();
+        let tagged = |tag_value: &str| Shape::Tagged {
+            tag: "type".to_string(),
+            variants: vec![TaggedVariant::new(tag_value.to_string(), Vec::new(), &[])],
+        };
+
+        let mut merged = tagged("page-view");
+        for tag_value in ["page_view", "PageView", "page-view"] {
+            merged = merge(merged, tagged(tag_value), "x", &flags).unwrap();
+        }
+
+        let Shape::Tagged { variants, .. } = merged else {
+            panic!("expected a tagged shape, got {:?}", merged);
+        };
+
+        let names = variants
+            .iter()
+            .map(|variant| (variant.tag_value.as_str(), variant.name.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            [
+                ("page-view", "PageView"),
+                ("page_view", "PageView2"),
+                ("PageView", "PageView3"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,9 @@
+//! Checks the errors the macros give for inputs they can't generate types from.
+//!
+//! The expected messages are in `tests/ui/*.stderr`, `TRYBUILD=overwrite cargo test` updates them.
+
+#[test]
+fn compile_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,832 @@
+//! Generates types with every kind of shape and flag, then checks they read and write their samples back.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+/// Reads a value as `T` and checks it's written back unchanged
+fn round_trip<T: DeserializeOwned + Serialize>(value: Value) -> T {
+    let parsed = serde_json::from_value::<T>(value.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+    parsed
+}
+
+mod from_file {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Company @debug @file("examples/fixtures/company.json"));
+
+    #[test]
+    fn reads_the_file() {
+        let company: Company =
+            serde_json::from_str(include_str!("../examples/fixtures/company.json")).unwrap();
+
+        assert_eq!(company.company_name, "Acme Corp");
+        assert_eq!(
+            serde_json::to_value(&company.tags).unwrap(),
+            json!(["industrial", "manufacturing"])
+        );
+        round_trip::<Company>(serde_json::to_value(&company).unwrap());
+    }
+}
+
+mod syntax {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Single @debug { "name": "ana", "admin": true });
+    json2struct!(Arrows @debug { "name" => "ana", "admin" => true });
+    json2struct!(Pasted @debug, r#"{ "name": "ana", "admin": true }"#);
+    json2struct!(Doubled @debug {{ "name": "ana", "admin": true }});
+
+    #[test]
+    fn reads_every_form() {
+        let value = json!({ "name": "bo", "admin": false });
+
+        round_trip::<Single>(value.clone());
+        round_trip::<Arrows>(value.clone());
+        round_trip::<Pasted>(value.clone());
+        round_trip::<Doubled>(value);
+    }
+}
+
+mod integers {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Counter { "id": 1, "big": 18446744073709551615, "ratio": 0.5 });
+    json2struct!(Narrow @int(i32) { "id": 1, "big": 5000000000 });
+
+    #[test]
+    fn keeps_integers_apart_from_floats() {
+        let counter = round_trip::<Counter>(json!({
+            "id": 7,
+            "big": 18446744073709551615u64,
+            "ratio": 0.25
+        }));
+
+        let _: i64 = counter.id;
+        let _: u64 = counter.big;
+        let _: f64 = counter.ratio;
+    }
+
+    #[test]
+    fn picks_the_width_with_int() {
+        let narrow = round_trip::<Narrow>(json!({ "id": 7, "big": 5000000000i64 }));
+
+        let _: i32 = narrow.id;
+        let _: i64 = narrow.big;
+    }
+}
+
+mod arrays_of_objects {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Team @debug {
+        "members": [{ "name": "ana", "roles": [{ "title": "lead" }] }]
+    });
+
+    #[test]
+    fn types_the_elements() {
+        let team = round_trip::<Team>(json!({
+            "members": [{ "name": "bo", "roles": [{ "title": "dev" }, { "title": "ops" }] }]
+        }));
+
+        assert_eq!(team.members[0].name, "bo");
+        assert_eq!(team.members[0].roles[1].title, "ops");
+    }
+}
+
+mod nested {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Company @debug {
+        "name": "Acme",
+        "founded": 1999,
+        "rating": 4.5,
+        "public": true,
+        "tags": ["tools", "anvils"],
+        "employees": [
+            { "id": 1, "details": { "email": "a@acme.test" } },
+            { "id": 2, "details": { "email": "b@acme.test" }, "manager": true }
+        ]
+    });
+
+    #[test]
+    fn round_trips() {
+        let company = round_trip::<Company>(json!({
+            "name": "Acme",
+            "founded": 1999,
+            "rating": 4.5,
+            "public": true,
+            "tags": ["tools"],
+            "employees": [
+                { "id": 1, "details": { "email": "a@acme.test" } },
+                { "id": 2, "details": { "email": "b@acme.test" }, "manager": true }
+            ]
+        }));
+
+        let employees: &Vec<Employee> = &company.employees;
+        let _: &Details = &employees[0].details;
+        assert_eq!(employees[0].manager, None);
+        assert_eq!(employees[1].manager, Some(true));
+    }
+}
+
+mod prelude_names {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Rpc {
+        "result": { "ok": true },
+        "string": { "v": "a" },
+        "option": { "v": 1 },
+        "vec": [{ "v": 2.5 }],
+        "box": { "v": null }
+    });
+
+    fn check(rpc: &Rpc) -> Result<Option<Vec<String>>, Box<str>> {
+        Ok(Some(vec![rpc.string.v.clone()]))
+    }
+
+    #[test]
+    fn keeps_the_prelude() {
+        let rpc = round_trip::<Rpc>(json!({
+            "result": { "ok": true },
+            "string": { "v": "a" },
+            "option": { "v": 1 },
+            "vec": [{ "v": 2.5 }],
+            "box": { "v": null }
+        }));
+
+        let _: (RpcResult, RpcOption, RpcBox) =
+            (rpc.result.clone(), rpc.option.clone(), rpc.r#box.clone());
+        let _: Vec<RpcVec> = rpc.vec.clone();
+        assert_eq!(check(&rpc), Ok(Some(vec!["a".to_string()])));
+    }
+}
+
+mod merged {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Reading @samples([
+        { "value": 1, "unit": "c", "note": null },
+        { "value": 2.5, "unit": null, "sensor": "s-1" }
+    ]));
+
+    #[test]
+    fn widens_and_makes_optional() {
+        let reading = round_trip::<Reading>(json!({ "value": 2.5, "unit": "c", "note": null }));
+
+        let _: f64 = reading.value;
+        let _: Option<String> = reading.unit;
+        let _: Option<String> = reading.sensor;
+        assert_eq!(reading.note, None);
+    }
+}
+
+mod null_hints {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Member { "name": "ana", "nickname": null as String });
+    json2struct!(Visit @samples([{ "referrer": null }, { "referrer": "search" }]));
+
+    #[test]
+    fn types_null_fields() {
+        let member = round_trip::<Member>(json!({ "name": "ana", "nickname": "an" }));
+        let _: Option<String> = member.nickname;
+
+        let visit = round_trip::<Visit>(json!({}));
+        let _: Option<String> = visit.referrer;
+    }
+}
+
+mod annotations {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Server {
+        "port": 8080 as u16,
+        "host": "127.0.0.1" as std::net::Ipv4Addr,
+        "tags": [] as Vec<String>,
+        "weight": 1: f32
+    });
+
+    #[test]
+    fn uses_the_types() {
+        let server = round_trip::<Server>(json!({
+            "port": 80,
+            "host": "10.0.0.1",
+            "tags": ["a"],
+            "weight": 0.5
+        }));
+
+        let _: u16 = server.port;
+        assert_eq!(server.host, std::net::Ipv4Addr::new(10, 0, 0, 1));
+        let _: Vec<String> = server.tags;
+        let _: f32 = server.weight;
+    }
+}
+
+mod numbers {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Point { "x": -5, "y": -0.25, "scale": 1e-9, "area": 2.5E3 });
+
+    #[test]
+    fn reads_signs_and_exponents() {
+        let point = round_trip::<Point>(json!({ "x": -3, "y": 0.5, "scale": 0.001, "area": 1.5 }));
+
+        let _: i64 = point.x;
+        let _: f64 = point.y;
+        let _: f64 = point.scale;
+        let _: f64 = point.area;
+    }
+}
+
+mod visibility {
+    use super::*;
+
+    mod types {
+        use json_to_struct::json2struct;
+
+        json2struct!(Account @pub @field_vis(pub) { "id": 1, "owner": { "name": "ana" } });
+        json2struct!(Session @vis(pub(crate)) @field_vis(pub(crate)) { "token": "t" });
+    }
+
+    #[test]
+    fn reaches_the_types_and_fields() {
+        let account = round_trip::<types::Account>(json!({ "id": 2, "owner": { "name": "bo" } }));
+        assert_eq!(account.owner.name, "bo");
+
+        let session = round_trip::<types::Session>(json!({ "token": "x" }));
+        assert_eq!(session.token, "x");
+    }
+}
+
+mod renamed {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Profile {
+        "userId": 1,
+        "displayName": "ana",
+        "type": "admin",
+        "2fa": false,
+        "HTTPStatus": 200,
+        "½": { "a": 1 }
+    });
+
+    #[test]
+    fn keeps_the_keys() {
+        let profile = round_trip::<Profile>(json!({
+            "userId": 1,
+            "displayName": "ana",
+            "type": "admin",
+            "2fa": false,
+            "HTTPStatus": 200,
+            "½": { "a": 1 }
+        }));
+
+        assert_eq!(profile.user_id, 1);
+        assert_eq!(profile.r#type, "admin");
+        assert!(!profile.field_2fa);
+        assert_eq!(profile.http_status, 200);
+        let _: V = profile.field;
+    }
+}
+
+mod shared {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Order {
+        "billing": { "street": "1 Main St" },
+        "shipping": { "street": "2 Side St" },
+        "home_address": { "city": "Anytown" },
+        "work_address": { "city": "Othertown" }
+    });
+
+    #[test]
+    fn shares_identical_objects() {
+        let order = round_trip::<Order>(json!({
+            "billing": { "street": "1 Main St" },
+            "shipping": { "street": "2 Side St" },
+            "home_address": { "city": "Anytown" },
+            "work_address": { "city": "Othertown" }
+        }));
+
+        let _: [Billing; 2] = [order.billing, order.shipping];
+        let _: [Address; 2] = [order.home_address, order.work_address];
+    }
+}
+
+mod stored_json {
+    use json_to_struct::json2struct;
+
+    json2struct!(Point @store_json @sample {
+        "x": -0,
+        "y": 1e-9,
+        "z": -0.0,
+        "w": -0 as f64,
+        "big": 18446744073709551615
+    });
+
+    #[test]
+    fn keeps_the_numbers_as_written() {
+        assert_eq!(
+            POINT_JSON_TEXT,
+            r#"{"x":-0,"y":1e-9,"z":-0.0,"w":-0,"big":18446744073709551615}"#
+        );
+        assert_eq!(POINT_JSON_VALUE.to_string(), POINT_JSON_TEXT);
+    }
+
+    #[test]
+    fn builds_the_numbers() {
+        let point = Point::sample();
+
+        assert_eq!(point.x, 0);
+        assert!(point.w.is_sign_negative());
+        assert_eq!(point.big, u64::MAX);
+    }
+}
+
+mod alias {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Contact @camel @alias { "first_name": "ana", "home_city": "x" });
+
+    #[test]
+    fn reads_both_names() {
+        let contact: Contact =
+            serde_json::from_value(json!({ "first_name": "ana", "homeCity": "y" })).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&contact).unwrap(),
+            json!({ "firstName": "ana", "homeCity": "y" })
+        );
+        round_trip::<Contact>(json!({ "firstName": "bo", "homeCity": "z" }));
+    }
+}
+
+mod rename_styles {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Header @rename_all("kebab-case") { "content_type": "json", "max_age": 1 });
+    json2struct!(Translator @rename_deserialize("camelCase") @rename_serialize("SCREAMING_SNAKE_CASE") {
+        "userId": 1
+    });
+
+    #[test]
+    fn renames_with_the_style() {
+        round_trip::<Header>(json!({ "content-type": "xml", "max-age": 2 }));
+    }
+
+    #[test]
+    fn translates_between_styles() {
+        let translator: Translator = serde_json::from_value(json!({ "userId": 3 })).unwrap();
+
+        assert_eq!(translator.user_id, 3);
+        assert_eq!(
+            serde_json::to_value(&translator).unwrap(),
+            json!({ "USER_ID": 3 })
+        );
+    }
+}
+
+mod detected_style {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Account { "userId": 1, "displayName": "ana" });
+    json2struct!(Cache { "max-age": 1, "content-type": "x" });
+    json2struct!(Mixed { "userId": 1, "display_name": "ana" });
+
+    #[test]
+    fn round_trips_the_detected_style() {
+        let account = round_trip::<Account>(json!({ "userId": 2, "displayName": "bo" }));
+        assert_eq!(account.display_name, "bo");
+
+        let cache = round_trip::<Cache>(json!({ "max-age": 3, "content-type": "y" }));
+        assert_eq!(cache.max_age, 3);
+
+        let mixed = round_trip::<Mixed>(json!({ "userId": 4, "display_name": "cy" }));
+        assert_eq!(mixed.user_id, 4);
+    }
+}
+
+mod untagged {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Post @untagged {
+        "tags": ["a", 1, { "name": "b" }]
+    });
+
+    #[test]
+    fn round_trips() {
+        round_trip::<Post>(json!({ "tags": ["x", 2, { "name": "y" }] }));
+    }
+}
+
+mod tagged {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Stream @tagged("type") {
+        "events": [
+            { "type": "click", "x": 1 },
+            { "type": "page_view", "url": "/" }
+        ]
+    });
+
+    #[test]
+    fn round_trips() {
+        let stream = round_trip::<Stream>(json!({
+            "events": [{ "type": "page_view", "url": "/home" }, { "type": "click", "x": 3 }]
+        }));
+
+        assert!(matches!(stream.events[0], Event::PageView(_)));
+    }
+}
+
+mod tagged_same_name {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Log @sample @tagged("kind") {
+        "entries": [
+            { "kind": "page-view", "url": "/" },
+            { "kind": "page_view", "path": "/home" }
+        ]
+    });
+
+    #[test]
+    fn numbers_the_variants() {
+        let log = round_trip::<Log>(json!({
+            "entries": [{ "kind": "page_view", "path": "/" }, { "kind": "page-view", "url": "/" }]
+        }));
+
+        assert!(matches!(log.entries[0], Entry::PageView2(_)));
+        assert!(matches!(Log::sample().entries[0], Entry::PageView(_)));
+    }
+}
+
+mod lenient {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Setting @lenient {
+        "values": [1, "two"]
+    });
+
+    #[test]
+    fn round_trips() {
+        let setting = round_trip::<Setting>(json!({ "values": [true, 2] }));
+        let _: Vec<Value> = setting.values;
+    }
+}
+
+mod sample {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Config @debug @sample @camel {
+        "appName": "demo",
+        "port": 8080,
+        "database": { "url": "postgres://localhost", "poolSize": 5 }
+    });
+
+    #[test]
+    fn sample_round_trips() {
+        let config = Config::sample();
+        let value = serde_json::to_value(&config).unwrap();
+
+        assert_eq!(round_trip::<Config>(value).database.pool_size, 5);
+    }
+}
+
+mod default_from_sample {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Config @debug @default_from_sample @camel {
+        "appName": "demo",
+        "port": 8080,
+        "database": { "url": "postgres://localhost", "poolSize": 5 }
+    });
+
+    #[test]
+    fn default_matches_the_sample() {
+        let config = Config::default();
+
+        assert_eq!(config.app_name, "demo");
+        assert_eq!(config.database.url, "postgres://localhost");
+    }
+
+    json2struct!(Server @debug @sample @default_from_sample {
+        "host": "127.0.0.1" as std::net::Ipv4Addr,
+        "port": 8080 as u16
+    });
+
+    #[test]
+    fn deserializes_types_without_literals() {
+        assert_eq!(Server::default().host, std::net::Ipv4Addr::LOCALHOST);
+        assert_eq!(Server::sample().host, std::net::Ipv4Addr::LOCALHOST);
+        assert_eq!(Server::default().port, 8080);
+    }
+
+    #[test]
+    fn fills_missing_fields() {
+        let config: Config = serde_json::from_value(json!({ "port": 9000 })).unwrap();
+
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.database.pool_size, 5);
+    }
+}
+
+mod builder {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Config @debug @builder @camel {
+        "appName": "demo",
+        "port": 8080,
+        "database": { "url": "postgres://localhost", "poolSize": 5 }
+    });
+
+    #[test]
+    fn builder_builds() {
+        let config = Config::builder()
+            .app_name("api")
+            .port(80)
+            .database_with(|database| database.url("sqlite::memory:").pool_size(1))
+            .build()
+            .unwrap();
+
+        round_trip::<Config>(serde_json::to_value(&config).unwrap());
+    }
+
+    #[test]
+    fn lists_missing_fields() {
+        let error = Config::builder().port(1).build().unwrap_err();
+
+        assert_eq!(error.missing, ["app_name", "database"]);
+    }
+}
+
+mod fields {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Config @debug @sample @fields @camel {
+        "appName": "demo",
+        "port": 8080,
+        "database": { "url": "postgres://localhost", "poolSize": 5 }
+    });
+
+    #[test]
+    fn fields_get_and_set() {
+        let mut config = Config::sample();
+
+        config
+            .set(ConfigField::DatabasePoolSize, json!(10))
+            .unwrap();
+
+        assert_eq!(config.get(ConfigField::DatabasePoolSize), json!(10));
+        assert_eq!(
+            ConfigField::DatabasePoolSize.pointer(),
+            "/database/poolSize"
+        );
+    }
+}
+
+mod json_schema {
+    use json_to_struct::json2struct;
+
+    json2struct!(Config @debug @sample @json_schema @camel {
+        "appName": "demo",
+        "port": 8080,
+        "database": { "url": "postgres://localhost", "poolSize": 5 }
+    });
+
+    #[test]
+    fn schema_describes_the_sample() {
+        let schema = Config::json_schema();
+
+        assert_eq!(schema["properties"]["appName"]["type"], "string");
+        assert_eq!(
+            schema["examples"][0],
+            serde_json::to_value(Config::sample()).unwrap()
+        );
+    }
+}
+
+mod builder_names {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Job @debug @builder {
+        "build": "release",
+        "details": { "lvl": 1 },
+        "details_with": "x",
+        "details_builder": { "tool": "cargo" },
+        "missing": true
+    });
+
+    fn complete() -> JobBuilder {
+        Job::builder()
+            .build_2("debug")
+            .details_with("y")
+            .details_builder_with(|tool| tool.tool("make"))
+            .missing(false)
+    }
+
+    #[test]
+    fn keeps_setters_apart() {
+        let job = complete()
+            .details_with_2(|details| details.lvl(2))
+            .build()
+            .unwrap();
+
+        let _: DetailsBuilder = Details::builder();
+        let _: JobDetailsBuilder = job.details_builder.clone();
+        round_trip::<Job>(serde_json::to_value(&job).unwrap());
+    }
+
+    #[test]
+    fn reports_nested_fields_once() {
+        let error = Job::builder()
+            .details_with_2(|details| details)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(
+            error.missing,
+            [
+                "details.lvl",
+                "build",
+                "details_with",
+                "details_builder",
+                "missing"
+            ]
+        );
+    }
+
+    #[test]
+    fn setting_a_field_again_replaces_its_errors() {
+        let broken = complete().details_with_2(|details| details);
+
+        let fixed = broken.clone().details_with_2(|details| details.lvl(3));
+        assert_eq!(fixed.build().unwrap().details.lvl, 3);
+
+        let set = broken.clone().details(Details { lvl: 4 });
+        assert_eq!(set.build().unwrap().details.lvl, 4);
+
+        assert_eq!(broken.build().unwrap_err().missing, ["details.lvl"]);
+    }
+}
+
+mod field_enum_names {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Job @debug @fields {
+        "details": { "lvl": 1 },
+        "details_field": { "name": "lvl" }
+    });
+
+    #[test]
+    fn keeps_field_enums_apart() {
+        let mut job: Job = serde_json::from_value(json!({
+            "details": { "lvl": 1 },
+            "details_field": { "name": "lvl" }
+        }))
+        .unwrap();
+
+        job.set(JobField::DetailsLvl, json!(2)).unwrap();
+        assert_eq!(job.details.get(DetailsField::Lvl), json!(2));
+
+        let _: JobDetailsField = job.details_field.clone();
+        assert_eq!(JobDetailsFieldField::ALL, [JobDetailsFieldField::Name]);
+        round_trip::<Job>(serde_json::to_value(&job).unwrap());
+    }
+}
+
+mod annotated_sample {
+    use super::*;
+    use json_to_struct::json2struct;
+
+    json2struct!(Settings @debug @sample {
+        "port": 8080 as u16,
+        "offset": -3 as i8,
+        "ratio": 1 as f32,
+        "tags": [] as Vec<String>,
+        "nickname": null as String,
+        "fallback": null as Option<u8>,
+        "point": [1, 2.5] as (i32, f64),
+        "bytes": [1, 2, 3] as [u8; 3],
+        "initial": "x" as char,
+        "boxed": 5 as Box<u64>,
+        "limits": { "cpu": 2 } as std::collections::BTreeMap<String, u8>,
+        "extra": { "a": [1, null] } as serde_json::Value
+    });
+
+    #[test]
+    fn builds_literals() {
+        let settings = Settings::sample();
+
+        assert_eq!(settings.port, 8080);
+        assert_eq!(settings.offset, -3);
+        assert!(settings.tags.is_empty());
+        assert_eq!(settings.nickname, None);
+        assert_eq!(settings.fallback, None);
+        assert_eq!(settings.point, (1, 2.5));
+        assert_eq!(settings.bytes, [1, 2, 3]);
+        assert_eq!(settings.initial, 'x');
+        assert_eq!(*settings.boxed, 5);
+        assert_eq!(settings.limits["cpu"], 2);
+        assert_eq!(settings.extra, json!({ "a": [1, null] }));
+
+        round_trip::<Settings>(serde_json::to_value(&settings).unwrap());
+    }
+}
+
+mod from_schema {
+    use super::*;
+    use json_to_struct::json2struct_schema;
+
+    json2struct_schema!(@debug "examples/fixtures/order.schema.json");
+
+    #[test]
+    fn round_trips() {
+        let order = round_trip::<Order>(json!({
+            "id": 1,
+            "status": "shipped",
+            "customer": { "name": "John", "vip": true },
+            "items": [{ "sku": "A-1", "quantity": 2, "price": 9.5 }],
+            "payment": { "method": "card", "last4": "4242" },
+            "billing": { "street": "1 Main St", "city": "Anytown" },
+            "metadata": { "gift": "yes" }
+        }));
+
+        let _: Customer = order.customer;
+        let _: [Option<Address>; 2] = [order.billing, order.shipping];
+    }
+}
+
+mod recursive_schema {
+    use super::*;
+    use json_to_struct::json2struct_schema;
+    use std::collections::HashMap;
+
+    json2struct_schema!(@debug @json_schema "examples/fixtures/tree.schema.json");
+
+    #[test]
+    fn refers_to_the_generated_types() {
+        let tree: Tree = serde_json::from_value(json!({
+            "root": {
+                "name": "a",
+                "children": [{ "name": "b", "children": [], "meta": { "owner": { "name": "c", "children": [] } } }],
+                "parent": { "name": "d", "children": [] },
+                "links": { "e": { "name": "e", "children": [] } }
+            },
+            "orphans": [{ "name": "g", "children": [] }],
+            "next": { "root": { "name": "f", "children": [] } },
+            "nested": [[], [[]]]
+        }))
+        .unwrap();
+
+        let _: &Vec<Node> = &tree.root.children;
+        let _: &Option<Box<Node>> = &tree.root.parent;
+        let _: &Option<HashMap<String, Node>> = &tree.root.links;
+        let _: &Option<Box<Node>> = &tree.root.children[0].meta.as_ref().unwrap().owner;
+        let _: &Option<Vec<Node>> = &tree.orphans;
+        let _: &Option<Box<Tree>> = &tree.next;
+        let _: &Option<Vec<serde_json::Value>> = &tree.nested;
+
+        round_trip::<Tree>(serde_json::to_value(&tree).unwrap());
+    }
+
+    #[test]
+    fn writes_recursive_schemas() {
+        let schema = Tree::json_schema();
+
+        assert_eq!(
+            schema["properties"]["root"],
+            json!({ "$ref": "#/$defs/Node" })
+        );
+        assert_eq!(
+            schema["$defs"]["Node"]["properties"]["children"]["items"],
+            json!({ "$ref": "#/$defs/Node" })
+        );
+        assert!(Tree::JSON_SCHEMA.contains(r##""$ref": "#""##));
+    }
+}
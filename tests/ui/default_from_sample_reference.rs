@@ -0,0 +1,7 @@
+use json_to_struct::json2struct;
+
+json2struct!(Server @default_from_sample {
+    "host": "localhost" as &'static str
+});
+
+fn main() {}
@@ -0,0 +1,7 @@
+use json_to_struct::json2struct;
+
+json2struct!(Ledger {
+    "balances": [-1, 18446744073709551615]
+});
+
+fn main() {}
@@ -0,0 +1,7 @@
+use json_to_struct::json2struct;
+
+json2struct!(Gauge @sample {
+    "ratio": 1e300 as f32
+});
+
+fn main() {}
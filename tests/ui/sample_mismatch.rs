@@ -0,0 +1,11 @@
+use json_to_struct::json2struct;
+
+json2struct!(Config @sample {
+    "port": "abc" as u16
+});
+
+json2struct!(Limits @sample {
+    "max": 300 as u8
+});
+
+fn main() {}